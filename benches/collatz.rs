@@ -0,0 +1,102 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::{BigUint, ToBigUint};
+use num_traits::One;
+
+use collatzprefixes::{
+    collatz::{self, EcfIter},
+    jump::JumpTable,
+    prefix,
+};
+
+/// Number of odd steps to take on each input.
+const STEPS: usize = 1000;
+
+/// A number with about 10^5 bits and a pseudo-random parity pattern.
+fn input() -> BigUint {
+    3.to_biguint().unwrap().pow(63093)
+}
+
+/// ECF prefix computed the way it was before in-place stepping,
+/// with a copy for every `3n+1` and a shift for every even step.
+fn ecf_baseline(mut n: BigUint, steps: usize) -> Vec<u32> {
+    let mut ans = Vec::new();
+    let mut twos = 0;
+
+    while ans.len() < steps {
+        if n.bit(0) {
+            ans.push(twos);
+            n = 3.to_biguint().unwrap() * n.clone() + BigUint::one();
+        } else {
+            twos += 1;
+            n >>= 1;
+        }
+    }
+
+    ans
+}
+
+/// Prefix iteration done the way it was before in-place stepping,
+/// dividing by a freshly allocated power of two at every step.
+fn iterate_baseline(mut n: BigUint, pf: &[u32]) -> BigUint {
+    n /= BigUint::one() << pf[0];
+    for i in 1..pf.len() {
+        n = 3.to_biguint().unwrap() * n.clone() + BigUint::one();
+        n /= BigUint::one() << (pf[i] - pf[i - 1]);
+    }
+    n
+}
+
+fn bench_ecf(c: &mut Criterion) {
+    let n = input();
+    let mut group = c.benchmark_group("ecf");
+    group.sample_size(10);
+
+    group.bench_function(BenchmarkId::new("baseline", n.bits()), |b| {
+        b.iter(|| ecf_baseline(black_box(n.clone()), STEPS))
+    });
+    group.bench_function(BenchmarkId::new("in-place", n.bits()), |b| {
+        b.iter(|| {
+            EcfIter::new(black_box(n.clone()))
+                .take(STEPS)
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_iterate(c: &mut Criterion) {
+    let n = input();
+    let pf = ecf_baseline(n.clone(), STEPS);
+    let mut group = c.benchmark_group("iterate");
+    group.sample_size(10);
+
+    group.bench_function(BenchmarkId::new("baseline", n.bits()), |b| {
+        b.iter(|| iterate_baseline(black_box(n.clone()), &pf))
+    });
+    group.bench_function(BenchmarkId::new("in-place", n.bits()), |b| {
+        b.iter(|| prefix::iterate(black_box(n.clone()), &pf))
+    });
+
+    group.finish();
+}
+
+fn bench_jump(c: &mut Criterion) {
+    let n = 3.to_biguint().unwrap().pow(1000);
+    let mut group = c.benchmark_group("length");
+
+    group.bench_function(BenchmarkId::new("step", n.bits()), |b| {
+        b.iter(|| collatz::length(black_box(n.clone())))
+    });
+    for k in [8, 16] {
+        let table = JumpTable::new(k);
+        group.bench_function(BenchmarkId::new(format!("jump-{}", k), n.bits()), |b| {
+            b.iter(|| collatz::length_with(black_box(n.clone()), &table))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_ecf, bench_iterate, bench_jump);
+criterion_main!(benches);
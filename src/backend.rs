@@ -0,0 +1,428 @@
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+use crate::CollatzIteration;
+
+/// An integer type that Collatz iterations can be carried out on.
+pub trait Backend: Sized {
+    /// Returns `true` if the number is 1.
+    fn is_one(&self) -> bool;
+
+    /// Returns `true` if the number is odd.
+    fn is_odd(&self) -> bool;
+
+    /// Shorthand for `n = n >> k`, done in-place.
+    fn shr(&mut self, k: u64);
+
+    /// Returns the number of trailing zeros, i.e. the length of the current run of even steps.
+    ///
+    /// Zero has no trailing zeros by this definition.
+    fn trailing_zeros(&self) -> u64;
+
+    /// Jumps over the current run of even steps, returning its length.
+    #[inline]
+    fn shr_trailing_zeros(&mut self) -> u64 {
+        let k = self.trailing_zeros();
+        self.shr(k);
+        k
+    }
+
+    /// Shorthand for `n = m*n + a`, returning `false` and leaving the number untouched on overflow.
+    fn checked_mul_add(&mut self, m: u64, a: u64) -> bool;
+
+    /// Shorthand for `n = 3*n + 1`, returning `false` and leaving the number untouched on overflow.
+    #[inline]
+    fn checked_three_x_plus_one(&mut self) -> bool {
+        self.checked_mul_add(3, 1)
+    }
+
+    /// Returns the number of bits needed to represent the number.
+    fn bits(&self) -> u64;
+
+    /// Returns the lowest `k` bits of the number, where `k <= 64`.
+    fn low_bits(&self, k: u32) -> u64;
+
+    /// Converts the number to a `BigUint`.
+    fn to_big(&self) -> BigUint;
+}
+
+impl Backend for u64 {
+    #[inline]
+    fn is_one(&self) -> bool {
+        *self == 1
+    }
+
+    #[inline]
+    fn is_odd(&self) -> bool {
+        *self & 1 == 1
+    }
+
+    #[inline]
+    fn shr(&mut self, k: u64) {
+        *self = u32::try_from(k)
+            .ok()
+            .and_then(|k| self.checked_shr(k))
+            .unwrap_or(0);
+    }
+
+    #[inline]
+    fn trailing_zeros(&self) -> u64 {
+        if *self == 0 {
+            0
+        } else {
+            u64::trailing_zeros(*self) as u64
+        }
+    }
+
+    #[inline]
+    fn checked_mul_add(&mut self, m: u64, a: u64) -> bool {
+        match self.checked_mul(m).and_then(|x| x.checked_add(a)) {
+            Some(x) => {
+                *self = x;
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn bits(&self) -> u64 {
+        (u64::BITS - self.leading_zeros()) as u64
+    }
+
+    #[inline]
+    fn low_bits(&self, k: u32) -> u64 {
+        *self & u64::MAX.checked_shr(u64::BITS - k).unwrap_or(0)
+    }
+
+    #[inline]
+    fn to_big(&self) -> BigUint {
+        BigUint::from(*self)
+    }
+}
+
+impl Backend for u128 {
+    #[inline]
+    fn is_one(&self) -> bool {
+        *self == 1
+    }
+
+    #[inline]
+    fn is_odd(&self) -> bool {
+        *self & 1 == 1
+    }
+
+    #[inline]
+    fn shr(&mut self, k: u64) {
+        *self = u32::try_from(k)
+            .ok()
+            .and_then(|k| self.checked_shr(k))
+            .unwrap_or(0);
+    }
+
+    #[inline]
+    fn trailing_zeros(&self) -> u64 {
+        if *self == 0 {
+            0
+        } else {
+            u128::trailing_zeros(*self) as u64
+        }
+    }
+
+    #[inline]
+    fn checked_mul_add(&mut self, m: u64, a: u64) -> bool {
+        match self
+            .checked_mul(m as u128)
+            .and_then(|x| x.checked_add(a as u128))
+        {
+            Some(x) => {
+                *self = x;
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn bits(&self) -> u64 {
+        (u128::BITS - self.leading_zeros()) as u64
+    }
+
+    #[inline]
+    fn low_bits(&self, k: u32) -> u64 {
+        (*self & u128::MAX.checked_shr(u128::BITS - k).unwrap_or(0)) as u64
+    }
+
+    #[inline]
+    fn to_big(&self) -> BigUint {
+        BigUint::from(*self)
+    }
+}
+
+impl Backend for BigUint {
+    #[inline]
+    fn is_one(&self) -> bool {
+        num_traits::One::is_one(self)
+    }
+
+    #[inline]
+    fn is_odd(&self) -> bool {
+        self.bit(0)
+    }
+
+    #[inline]
+    fn shr(&mut self, k: u64) {
+        *self >>= k;
+    }
+
+    #[inline]
+    fn trailing_zeros(&self) -> u64 {
+        BigUint::trailing_zeros(self).unwrap_or(0)
+    }
+
+    #[inline]
+    fn checked_mul_add(&mut self, m: u64, a: u64) -> bool {
+        *self *= m;
+        *self += a;
+        true
+    }
+
+    #[inline]
+    fn checked_three_x_plus_one(&mut self) -> bool {
+        self.three_x_plus_one();
+        true
+    }
+
+    #[inline]
+    fn bits(&self) -> u64 {
+        BigUint::bits(self)
+    }
+
+    #[inline]
+    fn low_bits(&self, k: u32) -> u64 {
+        self.iter_u64_digits().next().unwrap_or(0).low_bits(k)
+    }
+
+    #[inline]
+    fn to_big(&self) -> BigUint {
+        self.clone()
+    }
+}
+
+/// A number stored in the narrowest backend that can hold it.
+///
+/// When `3n+1` (or any `m*n + a`) overflows the current width, the number is promoted to the next one:
+/// `u64` to `u128`, and `u128` to `BigUint`. Shifting a number back into `u64` range demotes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Number {
+    U64(u64),
+    U128(u128),
+    Big(BigUint),
+}
+
+impl Number {
+    /// Moves the number to the next width.
+    fn promote(&mut self) {
+        *self = match self {
+            Number::U64(x) => Number::U128(*x as u128),
+            Number::U128(x) => Number::Big(BigUint::from(*x)),
+            Number::Big(_) => unreachable!("BigUint is the widest backend"),
+        }
+    }
+}
+
+impl From<BigUint> for Number {
+    fn from(n: BigUint) -> Self {
+        if let Some(x) = n.to_u64() {
+            Number::U64(x)
+        } else if let Some(x) = n.to_u128() {
+            Number::U128(x)
+        } else {
+            Number::Big(n)
+        }
+    }
+}
+
+impl From<Number> for BigUint {
+    fn from(n: Number) -> Self {
+        match n {
+            Number::U64(x) => BigUint::from(x),
+            Number::U128(x) => BigUint::from(x),
+            Number::Big(x) => x,
+        }
+    }
+}
+
+impl Backend for Number {
+    #[inline]
+    fn is_one(&self) -> bool {
+        match self {
+            Number::U64(x) => x.is_one(),
+            Number::U128(x) => x.is_one(),
+            Number::Big(x) => Backend::is_one(x),
+        }
+    }
+
+    #[inline]
+    fn is_odd(&self) -> bool {
+        match self {
+            Number::U64(x) => x.is_odd(),
+            Number::U128(x) => x.is_odd(),
+            Number::Big(x) => x.is_odd(),
+        }
+    }
+
+    #[inline]
+    fn shr(&mut self, k: u64) {
+        match self {
+            Number::U64(x) => x.shr(k),
+            Number::U128(x) => {
+                x.shr(k);
+                if let Ok(y) = u64::try_from(*x) {
+                    *self = Number::U64(y);
+                }
+            }
+            Number::Big(x) => {
+                x.shr(k);
+                if x.bits() <= 64 {
+                    *self = Number::U64(x.to_u64().unwrap());
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn trailing_zeros(&self) -> u64 {
+        match self {
+            Number::U64(x) => Backend::trailing_zeros(x),
+            Number::U128(x) => Backend::trailing_zeros(x),
+            Number::Big(x) => Backend::trailing_zeros(x),
+        }
+    }
+
+    /// Never overflows, as the number is promoted instead.
+    #[inline]
+    fn checked_mul_add(&mut self, m: u64, a: u64) -> bool {
+        let done = match self {
+            Number::U64(x) => x.checked_mul_add(m, a),
+            Number::U128(x) => x.checked_mul_add(m, a),
+            Number::Big(x) => x.checked_mul_add(m, a),
+        };
+        if !done {
+            self.promote();
+            self.checked_mul_add(m, a);
+        }
+        true
+    }
+
+    #[inline]
+    fn bits(&self) -> u64 {
+        match self {
+            Number::U64(x) => x.bits(),
+            Number::U128(x) => x.bits(),
+            Number::Big(x) => Backend::bits(x),
+        }
+    }
+
+    #[inline]
+    fn low_bits(&self, k: u32) -> u64 {
+        match self {
+            Number::U64(x) => x.low_bits(k),
+            Number::U128(x) => x.low_bits(k),
+            Number::Big(x) => x.low_bits(k),
+        }
+    }
+
+    #[inline]
+    fn to_big(&self) -> BigUint {
+        match self {
+            Number::U64(x) => x.to_big(),
+            Number::U128(x) => x.to_big(),
+            Number::Big(x) => x.clone(),
+        }
+    }
+}
+
+impl CollatzIteration for Number {
+    /// Shorthand for `n = 3*n + 1`, promoting the number on overflow.
+    #[inline]
+    fn three_x_plus_one(&mut self) {
+        let done = match self {
+            Number::U64(x) => x.checked_three_x_plus_one(),
+            Number::U128(x) => x.checked_three_x_plus_one(),
+            Number::Big(x) => x.checked_three_x_plus_one(),
+        };
+        if !done {
+            self.promote();
+            self.three_x_plus_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::ToBigUint;
+    use num_traits::One;
+
+    #[test]
+    fn test_number_promotion() {
+        struct Case {
+            n: BigUint,
+            width: fn(&Number) -> bool,
+        }
+        let cases = vec![
+            Case {
+                n: u64::MAX.to_biguint().unwrap() / 3u32 - 1u32,
+                width: |n| matches!(n, Number::U64(_)),
+            },
+            Case {
+                n: u64::MAX.to_biguint().unwrap() / 3u32,
+                width: |n| matches!(n, Number::U128(_)),
+            },
+            Case {
+                n: u128::MAX.to_biguint().unwrap() / 3u32,
+                width: |n| matches!(n, Number::Big(_)),
+            },
+        ];
+        for case in cases {
+            let mut n = Number::from(case.n.clone());
+            n.three_x_plus_one();
+            assert!((case.width)(&n), "Wrong width after 3n+1.");
+            assert_eq!(n.to_big(), 3u32 * case.n + 1u32, "Wrong result of 3n+1.");
+        }
+    }
+
+    #[test]
+    fn test_number_bits() {
+        let n = Number::from((BigUint::one() << 100) + 0b1011u32);
+        assert_eq!(n.bits(), 101, "Wrong bit count.");
+        assert_eq!(n.low_bits(3), 0b011, "Wrong low bits.");
+        assert_eq!(n.low_bits(64), 0b1011, "Wrong low bits.");
+        assert_eq!(
+            Number::U64(u64::MAX).low_bits(64),
+            u64::MAX,
+            "Wrong low bits."
+        );
+        assert_eq!(Number::U64(0).bits(), 0, "Wrong bit count.");
+
+        let mut n = Number::U64(u64::MAX);
+        n.checked_mul_add(u64::MAX, 5);
+        assert_eq!(
+            n.to_big(),
+            u64::MAX.to_biguint().unwrap() * u64::MAX + 5u32,
+            "Wrong result of m*n + a."
+        );
+    }
+
+    #[test]
+    fn test_number_demotion() {
+        let mut n = Number::from(BigUint::one() << 200);
+        n.shr(140);
+        assert_eq!(n, Number::U64(1 << 60), "Should be demoted to u64.");
+
+        let mut n = Number::from(BigUint::one() << 100);
+        assert_eq!(n.shr_trailing_zeros(), 100, "Wrong run of even steps.");
+        assert!(Backend::is_one(&n), "Should be shifted to 1.");
+    }
+}
@@ -1,85 +1,144 @@
+use std::{fmt, iter::FusedIterator, ops::Add, str::FromStr};
+
 use num_bigint::{BigUint, ToBigUint};
-use num_traits::One;
+use num_traits::{One, ToPrimitive, Zero};
 
-use crate::CollatzIteration;
+use crate::{
+    backend::{Backend, Number},
+    jump::JumpTable,
+    prefix::{self, check_strictly_ascending, parse_list, Prefix},
+    CollatzError, CollatzIteration,
+};
 
 /// Collatz length is the number of iterations it takes to reach n to 1.
-pub fn length(mut n: BigUint) -> usize {
+pub fn length(n: BigUint) -> usize {
+    let mut n = Number::from(n);
     let mut ans = 0;
 
-    while n != BigUint::one() {
-        ans += 1;
-        if n.bit(0) {
+    while !n.is_one() {
+        if n.is_odd() {
+            ans += 1;
             n.three_x_plus_one();
         } else {
-            n >>= 1;
+            // jump over the whole run of even steps
+            ans += n.shr_trailing_zeros().max(1) as usize;
         }
     }
 
     ans
 }
 
-/// Collatz Sequence is the array of numbers seen during iterations until 1 is reached.
-pub fn sequence(mut n: BigUint) -> Vec<BigUint> {
-    let mut ans = Vec::new();
+/// Collatz length computed `k` steps of the shortcut map at a time, see [`JumpTable`].
+///
+/// The result is the same as [`length`].
+pub fn length_with(n: BigUint, table: &JumpTable) -> usize {
+    let mut n = Number::from(n);
+    let mut ans = 0;
 
-    while n != BigUint::one() {
-        ans.push(n.clone());
-        if n.bit(0) {
-            n.three_x_plus_one();
-        } else {
-            n >>= 1;
-        }
+    while table.can_jump(&n) {
+        let jump = table.jump(&mut n);
+        ans += (table.k() + jump.odd) as usize;
     }
 
-    ans.push(BigUint::one());
+    ans + length(n.into())
+}
 
-    ans
+/// Sweeps the Collatz lengths of every number in `[start, end]`, see [`Lengths`].
+///
+/// At most `capacity` lengths are kept in memory to be reused by later trajectories.
+pub fn lengths(start: BigUint, end: BigUint, capacity: usize) -> Lengths {
+    Lengths::new(start, end, capacity)
 }
 
-/// Reduced Collatz Sequence is the array of odd numbers seen during iterations until 1 is reached.
-pub fn reduced_sequence(mut n: BigUint) -> Vec<BigUint> {
-    let mut ans = Vec::new();
+/// Fallible version of [`length`], rejecting `n = 0`.
+pub fn try_length(n: BigUint) -> Result<usize, CollatzError> {
+    check_positive(&n)?;
+    Ok(length(n))
+}
 
-    // if even, must be added at the start
-    if !n.bit(0) {
-        ans.push(n.clone())
-    }
+/// Collatz height is the largest number seen during iterations until 1 is reached.
+pub fn height(n: BigUint) -> BigUint {
+    let mut n = Number::from(n);
+    let mut ans = n.to_big();
 
-    while n != BigUint::one() {
-        if n.bit(0) {
-            ans.push(n.clone());
+    while !n.is_one() {
+        if n.is_odd() {
             n.three_x_plus_one();
+            // a peak can only be reached right after an odd step
+            let x = n.to_big();
+            if x > ans {
+                ans = x;
+            }
         } else {
-            n >>= 1;
+            n.shr_trailing_zeros();
         }
     }
 
-    ans.push(BigUint::one());
-
     ans
 }
 
+/// Fallible version of [`height`], rejecting `n = 0`.
+pub fn try_height(n: BigUint) -> Result<BigUint, CollatzError> {
+    check_positive(&n)?;
+    Ok(height(n))
+}
+
+/// Collatz Sequence is the array of numbers seen during iterations until 1 is reached.
+pub fn sequence(n: BigUint) -> Vec<BigUint> {
+    CollatzSequence::new(n).collect()
+}
+
+/// Fallible version of [`sequence`], rejecting `n = 0`.
+pub fn try_sequence(n: BigUint) -> Result<Vec<BigUint>, CollatzError> {
+    check_positive(&n)?;
+    Ok(sequence(n))
+}
+
+/// Reduced Collatz Sequence is the array of odd numbers seen during iterations until 1 is reached.
+pub fn reduced_sequence(n: BigUint) -> Vec<BigUint> {
+    ReducedSequence::new(n).collect()
+}
+
+/// Fallible version of [`reduced_sequence`], rejecting `n = 0`.
+pub fn try_reduced_sequence(n: BigUint) -> Result<Vec<BigUint>, CollatzError> {
+    check_positive(&n)?;
+    Ok(reduced_sequence(n))
+}
+
 /// Find ECF (Exponential Canonical Form) of a number.
-pub fn ecf(mut n: BigUint) -> Vec<u32> {
+pub fn ecf(n: BigUint) -> Vec<u32> {
+    EcfIter::new(n).collect()
+}
+
+/// ECF computed `k` steps of the shortcut map at a time, see [`JumpTable`].
+///
+/// The result is the same as [`ecf`].
+pub fn ecf_with(n: BigUint, table: &JumpTable) -> Vec<u32> {
+    let mut n = Number::from(n);
     let mut ans = Vec::new();
     let mut twos = 0;
 
-    while n != BigUint::one() {
-        if n.bit(0) {
-            ans.push(twos);
-            n += (n.clone() << 1) + BigUint::one();
-        } else {
+    while table.can_jump(&n) {
+        let jump = table.jump(&mut n);
+        for i in 0..table.k() {
+            if jump.parity >> i & 1 == 1 {
+                ans.push(twos);
+            }
+            // every step of the shortcut map has a division by two
             twos += 1;
-            n >>= 1;
         }
     }
 
-    ans.push(twos);
-
+    ans.extend(EcfIter::new(n.into()).map(|e| e + twos));
     ans
 }
 
+/// Fallible version of [`ecf`], rejecting `n = 0`.
+pub fn try_ecf(n: BigUint) -> Result<Vec<u32>, CollatzError> {
+    check_positive(&n)?;
+    Ok(ecf(n))
+}
+
 /// Compute a number from it's ECF.
 pub fn ecf_to_n(ecf: Vec<u32>) -> BigUint {
     let mut ans = BigUint::one();
@@ -92,6 +151,397 @@ pub fn ecf_to_n(ecf: Vec<u32>) -> BigUint {
     ans << ecf[0]
 }
 
+/// Fallible version of [`ecf_to_n`].
+///
+/// The ECF must be non-empty and strictly ascending, and every intermediate
+/// step must land on an integer other than 1 for it to belong to some number.
+pub fn try_ecf_to_n(ecf: Vec<u32>) -> Result<BigUint, CollatzError> {
+    if ecf.is_empty() {
+        return Err(CollatzError::EmptyEcf);
+    }
+    check_strictly_ascending(&ecf)?;
+
+    let mut ans = BigUint::one();
+    for i in (1..ecf.len()).rev() {
+        ans <<= ecf[i] - ecf[i - 1];
+        ans -= BigUint::one();
+
+        let three = 3.to_biguint().unwrap();
+        if !(&ans % &three).is_zero() {
+            return Err(CollatzError::InvalidEcf);
+        }
+        ans /= three;
+        if ans == BigUint::one() {
+            return Err(CollatzError::InvalidEcf);
+        }
+    }
+
+    Ok(ans << ecf[0])
+}
+
+/// An ECF, i.e. a non-empty strictly ascending list of numbers.
+///
+/// It is written as a list such as `[0, 1, 5]`, and maps to a number via [`prefix::to_num`] & [`prefix::from_num`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ecf(Vec<u32>);
+
+impl Ecf {
+    /// Creates an ECF, rejecting lists that are empty or not strictly ascending.
+    pub fn new(ecf: Vec<u32>) -> Result<Self, CollatzError> {
+        if ecf.is_empty() {
+            return Err(CollatzError::EmptyEcf);
+        }
+        check_strictly_ascending(&ecf)?;
+        Ok(Ecf(ecf))
+    }
+
+    /// Finds the ECF of a number, see [`ecf`].
+    pub fn of(n: BigUint) -> Self {
+        Ecf(ecf(n))
+    }
+
+    /// Computes the number that has this ECF, see [`try_ecf_to_n`].
+    ///
+    /// A strictly ascending list may still not belong to any number, which is reported as an error.
+    pub fn to_n(&self) -> Result<BigUint, CollatzError> {
+        try_ecf_to_n(self.0.clone())
+    }
+
+    /// Maps a number to an ECF, see [`prefix::from_num`].
+    pub fn from_num(k: BigUint) -> Result<Self, CollatzError> {
+        Ecf::new(prefix::from_num(k))
+    }
+
+    /// Maps the ECF to a number, see [`prefix::to_num`].
+    pub fn to_num(&self) -> BigUint {
+        prefix::to_num(self.0.clone())
+    }
+
+    pub fn as_slice(&self) -> &[u32] {
+        &self.0
+    }
+
+    pub fn into_vec(self) -> Vec<u32> {
+        self.0
+    }
+}
+
+impl TryFrom<Vec<u32>> for Ecf {
+    type Error = CollatzError;
+
+    fn try_from(ecf: Vec<u32>) -> Result<Self, CollatzError> {
+        Ecf::new(ecf)
+    }
+}
+
+impl From<Ecf> for Vec<u32> {
+    fn from(ecf: Ecf) -> Self {
+        ecf.0
+    }
+}
+
+impl From<Ecf> for Prefix {
+    /// An ECF is a prefix of its own number.
+    fn from(ecf: Ecf) -> Self {
+        Prefix::new(ecf.0).unwrap()
+    }
+}
+
+impl AsRef<[u32]> for Ecf {
+    fn as_ref(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl Add for &Ecf {
+    type Output = Ecf;
+
+    /// Attaching preserves strict ascension, see [`prefix::add`].
+    fn add(self, other: &Ecf) -> Ecf {
+        Ecf(prefix::add(&self.0, &other.0))
+    }
+}
+
+impl Add for Ecf {
+    type Output = Ecf;
+
+    fn add(self, other: Ecf) -> Ecf {
+        &self + &other
+    }
+}
+
+impl fmt::Display for Ecf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl FromStr for Ecf {
+    type Err = CollatzError;
+
+    fn from_str(s: &str) -> Result<Self, CollatzError> {
+        Ecf::new(parse_list(s)?)
+    }
+}
+
+/// Lazy iterator over the Collatz Sequence of a number, see [`sequence`].
+///
+/// The number of terms is not known until 1 is reached, so this iterator
+/// is neither `ExactSizeIterator` nor `DoubleEndedIterator`.
+#[derive(Debug, Clone)]
+pub struct CollatzSequence {
+    n: Option<Number>,
+}
+
+impl CollatzSequence {
+    pub fn new(n: BigUint) -> Self {
+        CollatzSequence {
+            n: Some(Number::from(n)),
+        }
+    }
+}
+
+impl Iterator for CollatzSequence {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        let n = self.n.as_mut()?;
+        let ans = n.to_big();
+
+        if n.is_one() {
+            self.n = None;
+        } else if n.is_odd() {
+            n.three_x_plus_one();
+        } else {
+            n.shr(1);
+        }
+
+        Some(ans)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.n {
+            Some(_) => (1, None),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl FusedIterator for CollatzSequence {}
+
+/// Lazy iterator over the Reduced Collatz Sequence of a number, see [`reduced_sequence`].
+///
+/// The number of terms is not known until 1 is reached, so this iterator
+/// is neither `ExactSizeIterator` nor `DoubleEndedIterator`.
+#[derive(Debug, Clone)]
+pub struct ReducedSequence {
+    n: Option<Number>,
+    first: bool,
+}
+
+impl ReducedSequence {
+    pub fn new(n: BigUint) -> Self {
+        ReducedSequence {
+            n: Some(Number::from(n)),
+            first: true,
+        }
+    }
+}
+
+impl Iterator for ReducedSequence {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        let n = self.n.as_mut()?;
+
+        // if even, must be yielded at the start
+        if std::mem::take(&mut self.first) && !n.is_odd() {
+            return Some(n.to_big());
+        }
+
+        while !n.is_odd() {
+            n.shr_trailing_zeros();
+        }
+
+        let ans = n.to_big();
+        if n.is_one() {
+            self.n = None;
+        } else {
+            n.three_x_plus_one();
+        }
+
+        Some(ans)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.n {
+            Some(_) => (1, None),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl FusedIterator for ReducedSequence {}
+
+/// Lazy iterator over the ECF of a number, see [`ecf`].
+///
+/// The number of elements is not known until 1 is reached, so this iterator
+/// is neither `ExactSizeIterator` nor `DoubleEndedIterator`.
+#[derive(Debug, Clone)]
+pub struct EcfIter {
+    n: Option<Number>,
+    twos: u32,
+}
+
+impl EcfIter {
+    pub fn new(n: BigUint) -> Self {
+        EcfIter {
+            n: Some(Number::from(n)),
+            twos: 0,
+        }
+    }
+}
+
+impl Iterator for EcfIter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let n = self.n.as_mut()?;
+
+        while !n.is_odd() {
+            self.twos += n.shr_trailing_zeros() as u32;
+        }
+
+        if n.is_one() {
+            self.n = None;
+        } else {
+            n.three_x_plus_one();
+        }
+
+        Some(self.twos)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.n {
+            Some(_) => (1, None),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl FusedIterator for EcfIter {}
+
+/// A table of the Collatz lengths of all numbers below a bound.
+///
+/// A trajectory is iterated only until it drops into the table, where the stored length is reused.
+/// The table is read-only once built, so it can be shared across threads.
+#[derive(Debug, Clone)]
+pub struct LengthTable {
+    memo: Vec<u32>,
+}
+
+impl LengthTable {
+    /// Builds a table of the lengths of every number below `bound`.
+    pub fn new(bound: usize) -> Self {
+        // index 0 is never used, but keeps the indices equal to the numbers
+        let mut table = LengthTable { memo: vec![0, 0] };
+        while table.memo.len() < bound {
+            let len = table.length(BigUint::from(table.memo.len()));
+            table.memo.push(len as u32);
+        }
+        table
+    }
+
+    /// Numbers below the bound have their length stored in the table.
+    pub fn bound(&self) -> usize {
+        self.memo.len()
+    }
+
+    /// Computes the length of `n`, iterating until the trajectory drops into the table.
+    ///
+    /// The result is the same as [`length`].
+    pub fn length(&self, n: BigUint) -> usize {
+        let bound = self.memo.len() as u64;
+        let mut n = Number::from(n);
+        let mut ans = 0;
+
+        loop {
+            if n.bits() <= 64 && n.low_bits(64) < bound {
+                return ans + self.memo[n.low_bits(64) as usize] as usize;
+            }
+            if n.is_odd() {
+                ans += 1;
+                n.three_x_plus_one();
+            } else {
+                ans += n.shr_trailing_zeros() as usize;
+            }
+        }
+    }
+}
+
+/// Streams the Collatz lengths of a range of numbers in order, see [`lengths`].
+///
+/// The lengths are found using a [`LengthTable`] of bounded size.
+/// While the sweep is within the table bound, its results extend the table.
+#[derive(Debug, Clone)]
+pub struct Lengths {
+    next: BigUint,
+    end: BigUint,
+    table: LengthTable,
+    capacity: usize,
+}
+
+impl Lengths {
+    /// Creates a sweep over `[start, end]`, with a table of at most `capacity` lengths.
+    ///
+    /// The table is filled up to the start of the range, or up to its capacity.
+    ///
+    /// Panics if `start` is zero.
+    pub fn new(start: BigUint, end: BigUint, capacity: usize) -> Self {
+        assert!(!start.is_zero(), "Range must start from 1.");
+
+        let capacity = capacity.max(2);
+        let fill = start.to_usize().unwrap_or(usize::MAX).min(capacity);
+        Lengths {
+            next: start,
+            end,
+            table: LengthTable::new(fill),
+            capacity,
+        }
+    }
+}
+
+impl Iterator for Lengths {
+    type Item = (BigUint, usize);
+
+    fn next(&mut self) -> Option<(BigUint, usize)> {
+        if self.next > self.end {
+            return None;
+        }
+
+        let n = self.next.clone();
+        let len = self.table.length(n.clone());
+        if self.table.bound() < self.capacity && n == BigUint::from(self.table.bound()) {
+            self.table.memo.push(len as u32);
+        }
+
+        self.next += 1u32;
+        Some((n, len))
+    }
+}
+
+impl FusedIterator for Lengths {}
+
+/// Returns an error if the number is zero.
+#[inline]
+fn check_positive(n: &BigUint) -> Result<(), CollatzError> {
+    if n.is_zero() {
+        Err(CollatzError::Zero)
+    } else {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,6 +612,11 @@ mod tests {
                 case.seq.len() - 1,
                 "Wrong sequence length."
             );
+            assert_eq!(
+                height(case.n.clone()),
+                case.seq.iter().max().unwrap().clone(),
+                "Wrong sequence height."
+            );
             assert_eq!(
                 reduced_sequence(case.n.clone()),
                 case.rseq,
@@ -211,4 +666,243 @@ mod tests {
             assert_eq!(ecf_to_n(case.ecf), case.n, "Wrong number from ECF.");
         }
     }
+
+    #[test]
+    fn test_collatz_backends() {
+        /// Collatz sequence computed purely with `BigUint`s.
+        fn sequence_big(mut n: BigUint) -> Vec<BigUint> {
+            let mut ans = vec![n.clone()];
+            while n != BigUint::one() {
+                if n.bit(0) {
+                    n.three_x_plus_one();
+                } else {
+                    n >>= 1;
+                }
+                ans.push(n.clone());
+            }
+            ans
+        }
+
+        let cases = vec![
+            // stays in u64
+            27.to_biguint().unwrap(),
+            // overflows u64 on the first step
+            u64::MAX.to_biguint().unwrap(),
+            // overflows u128 on the first step
+            u128::MAX.to_biguint().unwrap(),
+            // starts as a BigUint
+            (BigUint::one() << 200) - 1u32,
+        ];
+        for n in cases {
+            let seq = sequence_big(n.clone());
+            let rseq: Vec<BigUint> = seq
+                .iter()
+                .enumerate()
+                .filter(|(i, x)| x.bit(0) || *i == 0)
+                .map(|(_, x)| x.clone())
+                .collect();
+
+            assert_eq!(sequence(n.clone()), seq, "Wrong sequence.");
+            assert_eq!(length(n.clone()), seq.len() - 1, "Wrong length.");
+            assert_eq!(reduced_sequence(n.clone()), rseq, "Wrong reduced sequence.");
+            assert_eq!(ecf_to_n(ecf(n.clone())), n, "Wrong ECF.");
+        }
+    }
+
+    #[test]
+    fn test_collatz_jumps() {
+        let tables: Vec<JumpTable> = [1, 4, 12].into_iter().map(JumpTable::new).collect();
+        let cases = (1..1000u32).map(|n| n.to_biguint().unwrap()).chain([
+            u64::MAX.to_biguint().unwrap(),
+            (BigUint::one() << 300u32) - 1u32,
+            3.to_biguint().unwrap().pow(500),
+        ]);
+        for n in cases {
+            let len = length(n.clone());
+            let ecf_n = ecf(n.clone());
+            for table in &tables {
+                assert_eq!(
+                    length_with(n.clone(), table),
+                    len,
+                    "Wrong length for {}.",
+                    n
+                );
+                assert_eq!(ecf_with(n.clone(), table), ecf_n, "Wrong ECF for {}.", n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_collatz_lengths() {
+        struct Case {
+            start: BigUint,
+            end: BigUint,
+            capacity: usize,
+        }
+        let cases = vec![
+            // table grows along the sweep
+            Case {
+                start: BigUint::one(),
+                end: 2000.to_biguint().unwrap(),
+                capacity: 100,
+            },
+            // table is filled up to the start
+            Case {
+                start: 500.to_biguint().unwrap(),
+                end: 600.to_biguint().unwrap(),
+                capacity: 10000,
+            },
+            // range is beyond the table
+            Case {
+                start: u64::MAX.to_biguint().unwrap() - 20u32,
+                end: u64::MAX.to_biguint().unwrap() + 20u32,
+                capacity: 1000,
+            },
+            // empty range
+            Case {
+                start: 10.to_biguint().unwrap(),
+                end: 9.to_biguint().unwrap(),
+                capacity: 10,
+            },
+        ];
+        for case in cases {
+            let expected: Vec<(BigUint, usize)> =
+                std::iter::successors(Some(case.start.clone()), |n| Some(n + 1u32))
+                    .take_while(|n| *n <= case.end)
+                    .map(|n| (n.clone(), length(n)))
+                    .collect();
+            assert_eq!(
+                lengths(case.start, case.end, case.capacity).collect::<Vec<_>>(),
+                expected,
+                "Wrong lengths."
+            );
+
+            let table = LengthTable::new(case.capacity);
+            assert_eq!(
+                expected
+                    .iter()
+                    .map(|(n, _)| table.length(n.clone()))
+                    .collect::<Vec<_>>(),
+                expected.iter().map(|(_, len)| *len).collect::<Vec<_>>(),
+                "Wrong lengths using a shared table."
+            );
+        }
+    }
+
+    #[test]
+    fn test_collatz_iterators() {
+        // only the first few terms of a huge trajectory are computed
+        let n = (BigUint::one() << 100000u32) - 1u32;
+        let m = 3u32 * &n + 1u32;
+        assert_eq!(
+            CollatzSequence::new(n.clone()).take(3).collect::<Vec<_>>(),
+            vec![n.clone(), m.clone(), m.clone() >> 1],
+            "Wrong sequence prefix."
+        );
+        assert_eq!(
+            ReducedSequence::new(n.clone()).take(2).collect::<Vec<_>>(),
+            vec![n.clone(), m >> 1],
+            "Wrong reduced sequence prefix."
+        );
+        assert_eq!(
+            EcfIter::new(n).take(4).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3],
+            "Wrong ECF prefix."
+        );
+
+        let mut it = EcfIter::new(16.to_biguint().unwrap());
+        assert_eq!(it.next(), Some(4));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next(), None, "Iterator should be fused.");
+    }
+
+    #[test]
+    fn test_ecf_type() {
+        assert_eq!(Ecf::new(vec![]), Err(CollatzError::EmptyEcf));
+        assert_eq!("[]".parse::<Ecf>(), Err(CollatzError::EmptyEcf));
+        assert_eq!(
+            "[2, 3, 3]".parse::<Ecf>(),
+            Err(CollatzError::NotAscending { index: 2 })
+        );
+        assert_eq!(Ecf::from_num(BigUint::zero()), Err(CollatzError::EmptyEcf));
+
+        let e: Ecf = "[2,3,7]".parse().unwrap();
+        assert_eq!(
+            e,
+            Ecf::of(12.to_biguint().unwrap()),
+            "Wrong ECF from string."
+        );
+        assert_eq!(e.to_string(), "[2, 3, 7]", "Wrong display.");
+        assert_eq!(
+            e.to_n(),
+            Ok(12.to_biguint().unwrap()),
+            "Wrong number from ECF."
+        );
+        assert_eq!(
+            "[0, 3]".parse::<Ecf>().unwrap().to_n(),
+            Err(CollatzError::InvalidEcf),
+            "ECF should not belong to any number."
+        );
+        assert_eq!(
+            Ecf::from_num(e.to_num()),
+            Ok(e.clone()),
+            "Mapping should be bijective."
+        );
+        assert_eq!(
+            (&Ecf::of(3.to_biguint().unwrap()) + &e).as_slice(),
+            &[0, 1, 7, 8, 12],
+            "Wrong sum."
+        );
+        assert_eq!(Prefix::from(e).as_slice(), &[2, 3, 7]);
+    }
+
+    #[test]
+    fn test_collatz_errors() {
+        assert_eq!(try_length(BigUint::zero()), Err(CollatzError::Zero));
+        assert_eq!(try_sequence(BigUint::zero()), Err(CollatzError::Zero));
+        assert_eq!(
+            try_reduced_sequence(BigUint::zero()),
+            Err(CollatzError::Zero)
+        );
+        assert_eq!(try_ecf(BigUint::zero()), Err(CollatzError::Zero));
+        assert_eq!(try_height(BigUint::zero()), Err(CollatzError::Zero));
+        assert_eq!(try_length(27.to_biguint().unwrap()), Ok(111));
+
+        struct Case {
+            ecf: Vec<u32>,
+            err: CollatzError,
+        }
+        let cases = vec![
+            Case {
+                ecf: vec![],
+                err: CollatzError::EmptyEcf,
+            },
+            Case {
+                ecf: vec![0, 5, 1],
+                err: CollatzError::NotAscending { index: 2 },
+            },
+            Case {
+                ecf: vec![0, 1, 1],
+                err: CollatzError::NotAscending { index: 2 },
+            },
+            // 2^3 - 1 is not divisible by 3
+            Case {
+                ecf: vec![0, 3],
+                err: CollatzError::InvalidEcf,
+            },
+            // passes through 1 before the end
+            Case {
+                ecf: vec![0, 2, 4],
+                err: CollatzError::InvalidEcf,
+            },
+        ];
+        for case in cases {
+            assert_eq!(try_ecf_to_n(case.ecf), Err(case.err), "Expected an error.");
+        }
+        assert_eq!(
+            try_ecf_to_n(vec![2, 3, 7]),
+            Ok(12.to_biguint().unwrap()),
+            "Wrong number from ECF."
+        );
+    }
 }
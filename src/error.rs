@@ -0,0 +1,40 @@
+use std::{error::Error, fmt};
+
+/// Errors returned by the fallible (`try_*`) functions of this crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollatzError {
+    /// The number is zero, whose Collatz iterations never reach 1.
+    Zero,
+    /// The ECF has no elements.
+    EmptyEcf,
+    /// The element at `index` is not in ascending order w.r.t. the previous one.
+    NotAscending { index: usize },
+    /// The ECF does not belong to any number.
+    InvalidEcf,
+    /// The number does not reside at the given path.
+    PathMismatch,
+    /// The prefix stops applying to the number at the element at `step`.
+    PrefixMismatch { step: usize },
+    /// The input could not be parsed.
+    Parse,
+}
+
+impl fmt::Display for CollatzError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollatzError::Zero => write!(f, "number must be positive"),
+            CollatzError::EmptyEcf => write!(f, "ECF must not be empty"),
+            CollatzError::NotAscending { index } => {
+                write!(f, "element at index {} is not in ascending order", index)
+            }
+            CollatzError::InvalidEcf => write!(f, "ECF does not belong to any number"),
+            CollatzError::PathMismatch => write!(f, "number must be at this path"),
+            CollatzError::PrefixMismatch { step } => {
+                write!(f, "prefix does not apply to number at step {}", step)
+            }
+            CollatzError::Parse => write!(f, "could not parse input"),
+        }
+    }
+}
+
+impl Error for CollatzError {}
@@ -0,0 +1,210 @@
+use std::{collections::VecDeque, iter::FusedIterator};
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+/// Returns every number that reaches `n` in one Collatz step.
+///
+/// These are `2n`, and `(n-1)/3` if it is an odd number other than 1, i.e. `n = 4 (mod 6)` and `n != 4`.
+/// 1 is left out as the iterations stop there instead of going to 4.
+pub fn predecessors(n: &BigUint) -> Vec<BigUint> {
+    let mut ans = vec![n << 1u32];
+    if *n != BigUint::from(4u32) && (n % 6u32).to_u32() == Some(4) {
+        ans.push((n - 1u32) / 3u32);
+    }
+    ans
+}
+
+/// A node in the inverse tree, see [`InverseTree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub n: BigUint,
+    /// Number of steps from this node to the root.
+    pub depth: usize,
+    /// Odd steps on the way to the root, as the number of halvings before each, like the elements of an ECF.
+    ///
+    /// This is strictly ascending, as `3n+1` is always halved before the next odd step.
+    /// It iterates this node to the last odd number before the root, see [`crate::prefix::iterate`].
+    pub ecf: Vec<u32>,
+    /// Number of halvings from this node to the root.
+    ///
+    /// Appended to `ecf`, it gives the ECF relative to the root. When the root is 1, this is the ECF of the node.
+    /// The root may be reached right after an odd step, in which case it equals the last element of `ecf`.
+    pub twos: u32,
+}
+
+/// Streams the inverse Collatz tree rooted at a number in BFS order, down to a given depth.
+///
+/// The children of a node are its [`predecessors`]. The ECF of a child is found from its parent:
+/// `2m` divides by 2 once more, and `(m-1)/3` has an odd step right at the start.
+#[derive(Debug, Clone)]
+pub struct InverseTree {
+    queue: VecDeque<Node>,
+    depth: usize,
+}
+
+impl InverseTree {
+    /// Creates the tree rooted at `n`, with nodes at most `depth` steps away from it.
+    ///
+    /// Panics if `n` is zero.
+    pub fn new(n: BigUint, depth: usize) -> Self {
+        assert!(!n.is_zero(), "Root must be positive.");
+        InverseTree {
+            queue: VecDeque::from([Node {
+                n,
+                depth: 0,
+                ecf: vec![],
+                twos: 0,
+            }]),
+            depth,
+        }
+    }
+}
+
+impl Iterator for InverseTree {
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Node> {
+        let node = self.queue.pop_front()?;
+
+        if node.depth < self.depth {
+            for m in predecessors(&node.n) {
+                let (ecf, twos) = if m.bit(0) {
+                    // the parent is even, so its first odd step comes after at least one halving
+                    ([&[0], &node.ecf[..]].concat(), node.twos)
+                } else {
+                    (node.ecf.iter().map(|e| e + 1).collect(), node.twos + 1)
+                };
+                self.queue.push_back(Node {
+                    n: m,
+                    depth: node.depth + 1,
+                    ecf,
+                    twos,
+                });
+            }
+        }
+
+        Some(node)
+    }
+}
+
+impl FusedIterator for InverseTree {}
+
+/// Returns every number with Collatz length `k` in ascending order, see [`crate::collatz::length`].
+///
+/// These are the nodes at depth `k` of the inverse tree rooted at 1.
+pub fn with_length(k: usize) -> Vec<BigUint> {
+    let mut ans: Vec<BigUint> = InverseTree::new(BigUint::one(), k)
+        .filter(|node| node.depth == k)
+        .map(|node| node.n)
+        .collect();
+    ans.sort();
+    ans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collatz, prefix};
+    use num_bigint::ToBigUint;
+
+    /// Maps a given list of numbers to list of biguints.
+    fn to_biguints(nums: Vec<u32>) -> Vec<BigUint> {
+        nums.iter().map(|x| x.to_biguint().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_predecessors() {
+        struct Case {
+            n: u32,
+            preds: Vec<u32>,
+        }
+        let cases = vec![
+            // edge
+            Case {
+                n: 1,
+                preds: vec![2],
+            },
+            Case {
+                n: 4,
+                preds: vec![8],
+            },
+            // n = 4 (mod 6)
+            Case {
+                n: 16,
+                preds: vec![32, 5],
+            },
+            Case {
+                n: 10,
+                preds: vec![20, 3],
+            },
+            // otherwise
+            Case {
+                n: 8,
+                preds: vec![16],
+            },
+            Case {
+                n: 5,
+                preds: vec![10],
+            },
+        ];
+        for case in cases {
+            assert_eq!(
+                predecessors(&case.n.to_biguint().unwrap()),
+                to_biguints(case.preds),
+                "Wrong predecessors of {}.",
+                case.n
+            );
+        }
+    }
+
+    #[test]
+    fn test_inverse_tree() {
+        let nodes: Vec<Node> = InverseTree::new(BigUint::one(), 12).collect();
+        assert!(
+            nodes.windows(2).all(|w| w[0].depth <= w[1].depth),
+            "Nodes should be in BFS order."
+        );
+        for node in nodes {
+            assert_eq!(collatz::length(node.n.clone()), node.depth, "Wrong depth.");
+            assert_eq!(
+                collatz::ecf(node.n.clone()),
+                [&node.ecf[..], &[node.twos]].concat(),
+                "Wrong ECF."
+            );
+        }
+
+        // an even root, which some nodes reach right after an odd step
+        let root = 10.to_biguint().unwrap();
+        for node in InverseTree::new(root.clone(), 10) {
+            let m = prefix::try_iterate(node.n.clone(), &node.ecf).unwrap();
+            let reached = match node.ecf.last() {
+                Some(last) => (3u32 * m + 1u32) >> (node.twos - last),
+                None => m >> node.twos,
+            };
+            assert_eq!(
+                reached, root,
+                "Relative ECF should iterate {} to the root.",
+                node.n
+            );
+        }
+        let three = InverseTree::new(root, 1)
+            .find(|node| node.n == 3.to_biguint().unwrap())
+            .unwrap();
+        assert_eq!((three.ecf, three.twos), (vec![0], 0));
+    }
+
+    #[test]
+    fn test_with_length() {
+        assert_eq!(with_length(0), to_biguints(vec![1]));
+        assert_eq!(with_length(7), to_biguints(vec![3, 20, 21, 128]));
+
+        for k in 0..16 {
+            let expected: Vec<BigUint> = (1..=1u32 << k)
+                .map(BigUint::from)
+                .filter(|n| collatz::length(n.clone()) == k)
+                .collect();
+            assert_eq!(with_length(k), expected, "Wrong numbers with length {}.", k);
+        }
+    }
+}
@@ -2,16 +2,17 @@ use num_bigint::BigUint;
 use num_traits::One;
 
 use crate::{
+    path::Path,
     prefix::{add, iterate},
     utils::to_path,
     CollatzIteration,
 };
 
+/// Signature of the prefix finders in [`crate::riptree`] and [`crate::piptree`].
+pub type PrefixFinder = fn(n: BigUint, p: &Path) -> Vec<u32>;
+
 /// Find the ECF by iteratively extending the path until prefix iteration results in 1.
-pub fn path_extension(
-    n: &BigUint,
-    prefix_finder: fn(n: BigUint, p: &Vec<bool>) -> Vec<u32>,
-) -> Vec<u32> {
+pub fn path_extension(n: &BigUint, prefix_finder: PrefixFinder) -> Vec<u32> {
     let mut p = to_path(n);
     while iterate(n.clone(), &prefix_finder(n.clone(), &p)) != BigUint::one() {
         p.push(true);
@@ -21,13 +22,12 @@ pub fn path_extension(
 }
 
 /// Find the ECF by iteratively consuming the prefix until the iteration result is 1.
-pub fn prefix(n: &BigUint, prefix_finder: fn(n: BigUint, p: &Vec<bool>) -> Vec<u32>) -> Vec<u32> {
+pub fn prefix(n: &BigUint, prefix_finder: PrefixFinder) -> Vec<u32> {
     let mut ans = vec![];
     let mut cur_n = n.clone();
     loop {
         let pf = prefix_finder(cur_n.clone(), &to_path(&cur_n));
         ans = add(&ans, &pf);
-        println!("{:?} {}", pf, cur_n);
         cur_n = iterate(cur_n, &pf);
         if cur_n == BigUint::one() {
             return ans;
@@ -68,7 +68,7 @@ mod tests {
                 n: 38.to_biguint().unwrap(),
             },
             Case {
-                n: (186438726873 as i64).to_biguint().unwrap(),
+                n: 186438726873_i64.to_biguint().unwrap(),
             },
         ];
         for case in cases {
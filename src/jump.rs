@@ -0,0 +1,160 @@
+use crate::backend::{Backend, Number};
+
+/// Table size used by [`JumpTable::default`].
+pub const DEFAULT_K: u32 = 12;
+
+/// Largest supported table size.
+///
+/// A [`Jump`] takes 32 bytes, so the largest table has `2^20` of them in 32 MiB.
+pub const MAX_K: u32 = 20;
+
+/// The effect of `k` steps of the shortcut map `T(n) = (3n+1)/2 | n/2` on a residue class mod `2^k`.
+///
+/// For `n = 2^k * q + r`, it holds that `T^k(n) = 3^a * q + T^k(r)` where `a` is the number of odd steps.
+/// The parities of the steps only depend on `r`, so they are shared by the entire class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jump {
+    /// Bit `i` is set if the `i`-th step is odd.
+    pub parity: u64,
+    /// Number of odd steps, i.e. `a`.
+    pub odd: u32,
+    /// Multiplier of the quotient, i.e. `3^a`.
+    pub mul: u64,
+    /// Image of the residue, i.e. `T^k(r)`.
+    pub add: u64,
+}
+
+/// Precomputed [`Jump`]s for every residue mod `2^k`.
+///
+/// With a table, a single lookup plus one multiply-add replaces `k` steps of the shortcut map,
+/// which corresponds to `k + a` steps of the Collatz function.
+#[derive(Debug, Clone)]
+pub struct JumpTable {
+    k: u32,
+    jumps: Vec<Jump>,
+}
+
+impl JumpTable {
+    /// Builds a table for `k` steps at once, with `2^k` entries of 32 bytes each.
+    ///
+    /// Panics unless `1 <= k <= MAX_K`.
+    pub fn new(k: u32) -> Self {
+        assert!(
+            (1..=MAX_K).contains(&k),
+            "Table size must be in 1..={}.",
+            MAX_K
+        );
+
+        let jumps = (0..1u64 << k)
+            .map(|r| {
+                let mut jump = Jump {
+                    parity: 0,
+                    odd: 0,
+                    mul: 1,
+                    add: r,
+                };
+                for i in 0..k {
+                    if jump.add & 1 == 1 {
+                        jump.parity |= 1 << i;
+                        jump.odd += 1;
+                        jump.mul *= 3;
+                        jump.add = (3 * jump.add + 1) >> 1;
+                    } else {
+                        jump.add >>= 1;
+                    }
+                }
+                jump
+            })
+            .collect();
+
+        JumpTable { k, jumps }
+    }
+
+    /// Number of steps of the shortcut map done by a single jump.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Returns the jump for the residue `r` mod `2^k`.
+    pub fn get(&self, r: u64) -> &Jump {
+        &self.jumps[r as usize]
+    }
+
+    /// Returns `true` if a jump can be done on `n` without passing through 1, i.e. `n >= 2^k`.
+    #[inline]
+    pub(crate) fn can_jump(&self, n: &Number) -> bool {
+        n.bits() > self.k as u64
+    }
+
+    /// Replaces `n` by `T^k(n)` and returns the jump that was done.
+    #[inline]
+    pub(crate) fn jump(&self, n: &mut Number) -> &Jump {
+        let jump = self.get(n.low_bits(self.k));
+        n.shr(self.k as u64);
+        n.checked_mul_add(jump.mul, jump.add);
+        jump
+    }
+}
+
+impl Default for JumpTable {
+    fn default() -> Self {
+        JumpTable::new(DEFAULT_K)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_table() {
+        struct Case {
+            r: u64,
+            jump: Jump,
+        }
+        let table = JumpTable::new(3);
+        let cases = vec![
+            // 0 -> 0 -> 0 -> 0
+            Case {
+                r: 0,
+                jump: Jump {
+                    parity: 0b000,
+                    odd: 0,
+                    mul: 1,
+                    add: 0,
+                },
+            },
+            // 3 -> 5 -> 8 -> 4
+            Case {
+                r: 3,
+                jump: Jump {
+                    parity: 0b011,
+                    odd: 2,
+                    mul: 9,
+                    add: 4,
+                },
+            },
+            // 7 -> 11 -> 17 -> 26
+            Case {
+                r: 7,
+                jump: Jump {
+                    parity: 0b111,
+                    odd: 3,
+                    mul: 27,
+                    add: 26,
+                },
+            },
+        ];
+        for case in cases {
+            assert_eq!(*table.get(case.r), case.jump, "Wrong jump for {}.", case.r);
+        }
+
+        // 15 = 8 + 7 -> 23 -> 35 -> 53 = 27 + 26
+        let mut n = Number::U64(15);
+        assert!(table.can_jump(&n));
+        table.jump(&mut n);
+        assert_eq!(n, Number::U64(53), "Wrong result of jump.");
+        assert!(!table.can_jump(&Number::U64(7)));
+        assert_eq!(std::mem::size_of::<Jump>(), 32, "Wrong size of a jump.");
+    }
+}
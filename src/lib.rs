@@ -0,0 +1,40 @@
+//! Collatz Prefixes: a pattern among hailstone numbers.
+//!
+//! Read the [Gitbook](https://erhany96.gitbook.io/collatz-prefixes) for the theory.
+
+use num_bigint::BigUint;
+
+pub mod backend;
+pub mod collatz;
+pub mod error;
+pub mod inverse;
+pub mod iterative;
+pub mod jump;
+pub mod parity;
+pub mod path;
+pub mod piptree;
+pub mod prefix;
+pub mod records;
+pub mod render;
+pub mod residue;
+pub mod riptree;
+pub mod stats;
+pub mod sweep;
+pub mod trie;
+pub mod utils;
+
+pub use error::CollatzError;
+
+/// The odd step of the Collatz function.
+pub trait CollatzIteration {
+    fn three_x_plus_one(&mut self);
+}
+
+impl CollatzIteration for BigUint {
+    /// Shorthand for `n = 3*n + 1` in `BigUint`s, done in-place without allocating a copy.
+    #[inline]
+    fn three_x_plus_one(&mut self) {
+        *self *= 3u32;
+        *self += 1u32;
+    }
+}
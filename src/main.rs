@@ -1,36 +1,40 @@
-use std::env;
+use std::{
+    env, fmt,
+    io::{self, Write},
+    process,
+};
 
-use collatz::{ecf, reduced_sequence, sequence};
-use num_bigint::{BigUint, ToBigUint};
-use num_traits::One;
-use prefix::from_num;
-use utils::to_path;
-
-use crate::{collatz::length, prefix::to_num};
-
-trait CollatzIteration {
-    fn three_x_plus_one(&mut self);
-}
-
-impl CollatzIteration for BigUint {
-    /// Shorthand for `n = 3*n + 1` in `BigUint`s.
-    #[inline]
-    fn three_x_plus_one(&mut self) {
-        *self = 3.to_biguint().unwrap() * self.clone() + BigUint::one();
-    }
-}
-
-pub mod collatz;
-pub mod iterative;
-pub mod piptree;
-pub mod prefix;
-pub mod riptree;
-pub mod utils;
+use collatzprefixes::{
+    collatz::{ecf, height, length, reduced_sequence, sequence},
+    iterative,
+    piptree::{self, PipCursor},
+    prefix::{from_num, to_num},
+    records::{self, Record, Records},
+    render::{Label, SvgOptions, Tree, DEFAULT_LABELS},
+    riptree::{self, RipNode},
+    stats::TrajectoryStats,
+    sweep,
+    utils::to_path,
+};
+use num_bigint::BigUint;
+use num_traits::Zero;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("tree") {
+        run_tree(&args[2..]);
+        return;
+    }
+    if args.len() == 5 && args[2] == "--from" {
+        run_resume(&args[1], &args[3], &args[4]);
+        return;
+    }
+    if args.len() == 4 {
+        run_range(&args[1], &args[2], &args[3]);
+        return;
+    }
     if args.len() != 3 {
-        println!("Expected 2 arguments: function & number.");
+        println!("Expected 2 arguments: function & number, or 3 arguments: function & range.");
         return;
     }
 
@@ -42,7 +46,8 @@ fn main() {
         "seq" => println!("{:?}", sequence(n)),
         "rdseq" => println!("{:?}", reduced_sequence(n)),
         "ecf" => println!("{:?}", ecf(n)),
-        "path" => println!("{:?}", to_path(&n)),
+        "stats" => println!("{}", TrajectoryStats::of(n)),
+        "path" => println!("{}", to_path(&n)),
         "map" => println!("{:?}", from_num(n)),
         "pf-map" => println!(
             "{:?}",
@@ -54,6 +59,206 @@ fn main() {
         "ecf-pf-pip" => println!("{:?}", iterative::prefix(&n, piptree::prefix_find)),
         "ecf-path-rip" => println!("{:?}", iterative::path_extension(&n, riptree::prefix_find)),
         "ecf-path-pip" => println!("{:?}", iterative::path_extension(&n, piptree::prefix_find)),
+        "dot-pip" | "graphml-pip" | "svg-pip" if n.is_zero() => {
+            println!("Path length must be positive.")
+        }
+        "dot-rip" => print!("{}", rip_tree(&n).to_dot(DEFAULT_LABELS)),
+        "dot-pip" => print!("{}", pip_tree(&n).to_dot(PIP_LABELS)),
+        "graphml-rip" => print!("{}", rip_tree(&n).to_graphml(DEFAULT_LABELS)),
+        "graphml-pip" => print!("{}", pip_tree(&n).to_graphml(PIP_LABELS)),
+        "svg-rip" => print!("{}", rip_tree(&n).to_svg(&SvgOptions::default())),
+        "svg-pip" => print!(
+            "{}",
+            pip_tree(&n).to_svg(&SvgOptions {
+                labels: PIP_LABELS.to_vec(),
+                ..SvgOptions::default()
+            })
+        ),
         _ => println!("Unknown function."),
     }
 }
+
+/// Labels of PIPTree nodes, which also show the nature.
+const PIP_LABELS: &[Label] = &[Label::Number, Label::Prefix, Label::Nature];
+
+/// RIPTree from the root down to a depth.
+fn rip_tree(depth: &BigUint) -> Tree {
+    Tree::riptree(&RipNode::root(), depth.try_into().unwrap())
+}
+
+/// The whole PIPTree of paths of a length.
+fn pip_tree(len: &BigUint) -> Tree {
+    let len: usize = len.try_into().unwrap();
+    Tree::piptree(&PipCursor::root(len), len)
+}
+
+/// Runs a function on every number in `[start, end]`.
+///
+/// Records are printed as they are found, and everything else is swept on all cores and printed in order.
+fn run_range(function: &str, start: &str, end: &str) {
+    let start = start.parse::<BigUint>().unwrap();
+    let end = end.parse::<BigUint>().unwrap();
+    if start.is_zero() {
+        println!("Range must start from 1.");
+        return;
+    }
+
+    match function {
+        "rec-delay" => return print_records(records::delay(start, end)),
+        "rec-height" => return print_records(records::height(start, end)),
+        "rec-ecf" => return print_records(records::ecf(start, end)),
+        _ => {}
+    }
+
+    match function {
+        "len" => {
+            let table = sweep::length_table(&end);
+            print_range(&start, &end, |n| table.length(n).to_string())
+        }
+        "ecf" => print_range(&start, &end, |n| format!("{:?}", ecf(n))),
+        "pf-rip" => print_range(&start, &end, |n| {
+            format!("{:?}", riptree::prefix_find(n.clone(), &to_path(&n)))
+        }),
+        "pf-pip" => print_range(&start, &end, |n| {
+            format!("{:?}", piptree::prefix_find(n.clone(), &to_path(&n)))
+        }),
+        "ecf-pf-rip" => print_range(&start, &end, |n| {
+            format!("{:?}", iterative::prefix(&n, riptree::prefix_find))
+        }),
+        "ecf-pf-pip" => print_range(&start, &end, |n| {
+            format!("{:?}", iterative::prefix(&n, piptree::prefix_find))
+        }),
+        _ => println!("Unknown range function."),
+    }
+}
+
+/// Sweeps `[start, end]` on all cores, printing `n: line` for every number in order as soon as its chunk is done.
+fn print_range<F>(start: &BigUint, end: &BigUint, line: F)
+where
+    F: Fn(BigUint) -> String + Sync,
+{
+    let mut out = io::stdout().lock();
+    report(sweep::try_for_each(
+        start,
+        end,
+        |n| format!("{}: {}", n, line(n.clone())),
+        |line| writeln!(out, "{}", line),
+    ));
+}
+
+/// Reports an error while writing the output, except for a closed pipe such as `| head`, which just ends it.
+fn report(result: io::Result<()>) {
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("Could not write output: {}", e);
+            process::exit(1);
+        }
+        _ => {}
+    }
+}
+
+/// Resumes a scan for records right after a known record, up to `end`, see [`Records::resume`].
+fn run_resume(function: &str, last: &str, end: &str) {
+    let last = last.parse::<BigUint>().unwrap();
+    let end = end.parse::<BigUint>().unwrap();
+    if last.is_zero() {
+        println!("Record must be positive.");
+        return;
+    }
+
+    match function {
+        "rec-delay" => print_records(resume(last, end, length)),
+        "rec-height" => print_records(resume(last, end, height)),
+        "rec-ecf" => print_records(resume(last, end, records::ecf_length)),
+        _ => println!("Unknown record function."),
+    }
+}
+
+/// Scan for records after the number `last`, whose value is found with `value`.
+fn resume<V: Ord + Clone>(last: BigUint, end: BigUint, value: fn(BigUint) -> V) -> Records<V> {
+    let record = Record {
+        value: value(last.clone()),
+        n: last,
+    };
+    Records::resume(record, end, value)
+}
+
+fn print_records<V: Ord + Clone + fmt::Display>(records: Records<V>) {
+    let mut out = io::stdout().lock();
+    report(
+        records
+            .into_iter()
+            .try_for_each(|record| writeln!(out, "{}: {}", record.n, record.value)),
+    );
+}
+
+/// Prints a RIPTree or PIPTree in the terminal, from arguments `rip <depth> [--focus <n>]` or `pip <len> [--nature] [--focus <n>]`.
+///
+/// The size is the depth for RIPTree, and the path length for PIPTree. Only PIPTree nodes have a nature.
+/// With a focus, only the branch towards it is built, so deep trees can be printed too.
+fn run_tree(args: &[String]) {
+    let usage =
+        "Expected arguments: rip <depth> [--focus <n>], or pip <len> [--nature] [--focus <n>].";
+    if args.len() < 2 {
+        println!("{}", usage);
+        return;
+    }
+
+    let size: usize = args[1].parse().unwrap();
+    let mut labels = DEFAULT_LABELS.to_vec();
+    let mut focus = None;
+    let mut flags = args[2..].iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--nature" => labels.push(Label::Nature),
+            "--focus" => match flags.next() {
+                Some(n) => focus = Some(n.parse::<BigUint>().unwrap()),
+                None => {
+                    println!("{}", usage);
+                    return;
+                }
+            },
+            _ => {
+                println!("{}", usage);
+                return;
+            }
+        }
+    }
+
+    if args[0] == "pip" && size == 0 {
+        println!("Path length must be positive.");
+        return;
+    }
+    if args[0] == "rip" && labels.contains(&Label::Nature) {
+        println!("Nature only applies to PIPTree.");
+        return;
+    }
+
+    let (tree, focus) = match (args[0].as_str(), focus) {
+        ("rip", None) => (rip_tree(&size.into()), None),
+        ("pip", None) => (pip_tree(&size.into()), None),
+        ("rip", Some(n)) if !n.is_zero() => {
+            let p = to_path(&n);
+            (Tree::riptree_towards(&RipNode::root(), size, &p), Some(p))
+        }
+        ("pip", Some(n)) => match piptree::find_path(&n, size) {
+            Some(p) => (
+                Tree::piptree_towards(&PipCursor::root(size), size, &p),
+                Some(p),
+            ),
+            None => {
+                println!("{} is not in the PIPTree of paths of length {}.", n, size);
+                return;
+            }
+        },
+        ("rip", Some(_)) => {
+            println!("Number must be positive.");
+            return;
+        }
+        _ => {
+            println!("{}", usage);
+            return;
+        }
+    };
+    print!("{}", tree.to_text(&labels, focus.as_ref()));
+}
@@ -0,0 +1,286 @@
+use num_bigint::BigUint;
+
+use crate::{collatz, prefix::check_strictly_ascending, CollatzError};
+
+/// Standard parity vector of a number.
+///
+/// It has a bit for every step of the Collatz function until 1 is reached, set if the step is odd.
+/// For example, 3 has the ECF `[0, 1, 5]` and the standard parity vector `1010000`.
+pub fn parity_vector(n: BigUint) -> Vec<bool> {
+    parity_of(&collatz::ecf(n))
+}
+
+/// Shortcut parity vector of a number.
+///
+/// It has a bit for every step of the shortcut map `T(n) = (3n+1)/2 | n/2`, set if the step is odd.
+/// For example, 3 has the ECF `[0, 1, 5]` and the shortcut parity vector `11000`.
+pub fn shortcut_vector(n: BigUint) -> Vec<bool> {
+    shortcut_of(&collatz::ecf(n))
+}
+
+/// U/D string of a number.
+///
+/// It writes the shortcut parity vector with `U` for odd (up) and `D` for even (down) steps.
+/// For example, 3 has the ECF `[0, 1, 5]` and the U/D string `UUDDD`.
+pub fn ud_string(n: BigUint) -> String {
+    to_ud(&shortcut_vector(n))
+}
+
+/// Converts an ECF to the standard parity vector.
+///
+/// Every element but the last is an odd step after the even steps since the previous element,
+/// and the last element is 1 where the iterations stop.
+/// Lists that are empty or not strictly ascending are rejected.
+pub fn ecf_to_parity(ecf: &[u32]) -> Result<Vec<bool>, CollatzError> {
+    check_ecf(ecf)?;
+    Ok(parity_of(ecf))
+}
+
+/// Unchecked version of [`ecf_to_parity`], for ECFs that are known to be valid.
+fn parity_of(ecf: &[u32]) -> Vec<bool> {
+    let mut ans = Vec::new();
+    let mut twos = 0;
+
+    for (i, e) in ecf.iter().enumerate() {
+        ans.extend((twos..*e).map(|_| false));
+        if i + 1 < ecf.len() {
+            ans.push(true);
+        }
+        twos = *e;
+    }
+
+    ans
+}
+
+/// Converts a standard parity vector to an ECF.
+///
+/// The vector of a number never has two odd steps in a row, nor an odd step at the end,
+/// as `3n+1` is always even. Such vectors are rejected.
+pub fn parity_to_ecf(pv: &[bool]) -> Result<Vec<u32>, CollatzError> {
+    let mut ans = Vec::new();
+    let mut twos = 0;
+
+    for odd in pv {
+        if *odd {
+            ans.push(twos);
+        } else {
+            twos += 1;
+        }
+    }
+    ans.push(twos);
+
+    check_strictly_ascending(&ans)?;
+    Ok(ans)
+}
+
+/// Converts an ECF to the shortcut parity vector.
+///
+/// The `i`-th step of the shortcut map is odd if `i` is an element of the ECF, other than the last.
+/// Lists that are empty or not strictly ascending are rejected.
+pub fn ecf_to_shortcut(ecf: &[u32]) -> Result<Vec<bool>, CollatzError> {
+    check_ecf(ecf)?;
+    Ok(shortcut_of(ecf))
+}
+
+/// Unchecked version of [`ecf_to_shortcut`], for ECFs that are known to be valid.
+fn shortcut_of(ecf: &[u32]) -> Vec<bool> {
+    let (last, odds) = match ecf.split_last() {
+        Some(x) => x,
+        None => return vec![],
+    };
+
+    let mut ans = vec![false; *last as usize];
+    for e in odds {
+        ans[*e as usize] = true;
+    }
+    ans
+}
+
+/// Converts a shortcut parity vector to an ECF.
+pub fn shortcut_to_ecf(sv: &[bool]) -> Vec<u32> {
+    (0..sv.len() as u32)
+        .filter(|i| sv[*i as usize])
+        .chain(std::iter::once(sv.len() as u32))
+        .collect()
+}
+
+/// Converts a standard parity vector to the shortcut parity vector, by merging every odd step with the even step after it.
+pub fn parity_to_shortcut(pv: &[bool]) -> Result<Vec<bool>, CollatzError> {
+    Ok(shortcut_of(&parity_to_ecf(pv)?))
+}
+
+/// Converts a shortcut parity vector to the standard parity vector, by splitting every odd step into an odd and an even step.
+pub fn shortcut_to_parity(sv: &[bool]) -> Vec<bool> {
+    // the ECF of a shortcut parity vector is always valid
+    parity_of(&shortcut_to_ecf(sv))
+}
+
+/// Writes a shortcut parity vector as a U/D string.
+pub fn to_ud(sv: &[bool]) -> String {
+    sv.iter().map(|odd| if *odd { 'U' } else { 'D' }).collect()
+}
+
+/// Parses a U/D string to a shortcut parity vector.
+pub fn from_ud(s: &str) -> Result<Vec<bool>, CollatzError> {
+    s.chars()
+        .map(|c| match c {
+            'U' => Ok(true),
+            'D' => Ok(false),
+            _ => Err(CollatzError::Parse),
+        })
+        .collect()
+}
+
+/// Returns an error if the list is empty or not strictly ascending.
+fn check_ecf(ecf: &[u32]) -> Result<(), CollatzError> {
+    if ecf.is_empty() {
+        return Err(CollatzError::EmptyEcf);
+    }
+    check_strictly_ascending(ecf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::ToBigUint;
+    use num_traits::One;
+
+    /// Maps a string of 0s and 1s to a vector of bits.
+    fn to_bits(s: &str) -> Vec<bool> {
+        s.chars().map(|c| c == '1').collect()
+    }
+
+    #[test]
+    fn test_parity() {
+        struct Case {
+            n: BigUint,
+            ecf: Vec<u32>,
+            pv: Vec<bool>,
+            sv: Vec<bool>,
+            ud: String,
+        }
+        let cases = vec![
+            // edge
+            Case {
+                n: BigUint::one(),
+                ecf: vec![0],
+                pv: vec![],
+                sv: vec![],
+                ud: String::new(),
+            },
+            // power of two
+            Case {
+                n: 16.to_biguint().unwrap(),
+                ecf: vec![4],
+                pv: to_bits("0000"),
+                sv: to_bits("0000"),
+                ud: "DDDD".to_string(),
+            },
+            // odd number
+            Case {
+                n: 3.to_biguint().unwrap(),
+                ecf: vec![0, 1, 5],
+                pv: to_bits("1010000"),
+                sv: to_bits("11000"),
+                ud: "UUDDD".to_string(),
+            },
+            // even number
+            Case {
+                n: 12.to_biguint().unwrap(),
+                ecf: vec![2, 3, 7],
+                pv: to_bits("001010000"),
+                sv: to_bits("0011000"),
+                ud: "DDUUDDD".to_string(),
+            },
+        ];
+        for case in cases {
+            assert_eq!(
+                parity_vector(case.n.clone()),
+                case.pv,
+                "Wrong parity vector."
+            );
+            assert_eq!(
+                shortcut_vector(case.n.clone()),
+                case.sv,
+                "Wrong shortcut vector."
+            );
+            assert_eq!(ud_string(case.n.clone()), case.ud, "Wrong U/D string.");
+
+            assert_eq!(ecf_to_parity(&case.ecf), Ok(case.pv.clone()));
+            assert_eq!(parity_to_ecf(&case.pv), Ok(case.ecf.clone()));
+            assert_eq!(ecf_to_shortcut(&case.ecf), Ok(case.sv.clone()));
+            assert_eq!(shortcut_to_ecf(&case.sv), case.ecf);
+            assert_eq!(parity_to_shortcut(&case.pv), Ok(case.sv.clone()));
+            assert_eq!(shortcut_to_parity(&case.sv), case.pv);
+            assert_eq!(to_ud(&case.sv), case.ud);
+            assert_eq!(from_ud(&case.ud), Ok(case.sv));
+        }
+    }
+
+    #[test]
+    fn test_parity_roundtrip() {
+        for n in (1..1000u32).map(BigUint::from) {
+            let ecf = collatz::ecf(n.clone());
+            let pv = parity_vector(n.clone());
+            assert_eq!(
+                pv.len(),
+                collatz::length(n.clone()),
+                "Wrong parity vector length."
+            );
+            assert_eq!(
+                parity_to_ecf(&pv),
+                Ok(ecf.clone()),
+                "Wrong ECF from parity vector."
+            );
+            assert_eq!(
+                shortcut_to_ecf(&from_ud(&ud_string(n)).unwrap()),
+                ecf,
+                "Wrong ECF from U/D string."
+            );
+        }
+    }
+
+    #[test]
+    fn test_parity_errors() {
+        assert_eq!(
+            parity_to_ecf(&to_bits("0110")),
+            Err(CollatzError::NotAscending { index: 1 })
+        );
+        assert_eq!(
+            parity_to_ecf(&to_bits("01")),
+            Err(CollatzError::NotAscending { index: 1 })
+        );
+        assert_eq!(from_ud("UDX"), Err(CollatzError::Parse));
+
+        struct Case {
+            ecf: Vec<u32>,
+            err: CollatzError,
+        }
+        let cases = vec![
+            Case {
+                ecf: vec![],
+                err: CollatzError::EmptyEcf,
+            },
+            Case {
+                ecf: vec![3, 1],
+                err: CollatzError::NotAscending { index: 1 },
+            },
+            Case {
+                ecf: vec![0, 2, 2],
+                err: CollatzError::NotAscending { index: 2 },
+            },
+        ];
+        for case in cases {
+            assert_eq!(
+                ecf_to_parity(&case.ecf),
+                Err(case.err.clone()),
+                "Expected an error."
+            );
+            assert_eq!(
+                ecf_to_shortcut(&case.ecf),
+                Err(case.err),
+                "Expected an error."
+            );
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::{fmt, iter::FromIterator, ops::Index, str::FromStr};
+
+use num_bigint::BigUint;
+
+use crate::CollatzError;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A path in the prefix trees, packed into 64-bit words.
+///
+/// Each direction is a bit, where `false` is left (`L`) and `true` is right (`R`).
+/// The `i`-th direction is stored at bit `i % 64` of word `i / 64`, and unused bits
+/// of the last word are always zero.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Path {
+    /// Creates an empty path.
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    /// Creates a path of length `len` where every direction is `dir`.
+    pub fn filled(dir: bool, len: usize) -> Self {
+        let word = if dir { u64::MAX } else { 0 };
+        let mut p = Path {
+            words: vec![word; len.div_ceil(WORD_BITS)],
+            len,
+        };
+        p.mask();
+        p
+    }
+
+    /// Finds the path of a number.
+    ///
+    /// This is the flipped binary representation of `n - 1`, read from the least significant bit.
+    pub fn of(n: &BigUint) -> Self {
+        let m = n - 1u32;
+        let mut p = Path {
+            words: m.iter_u64_digits().map(|w| !w).collect(),
+            len: m.bits() as usize,
+        };
+        p.mask();
+        p
+    }
+
+    /// Finds the number at this path.
+    pub fn to_n(&self) -> BigUint {
+        let mut flipped = self.clone();
+        flipped.words.iter_mut().for_each(|w| *w = !*w);
+        flipped.mask();
+
+        let digits = flipped
+            .words
+            .iter()
+            .flat_map(|w| [*w as u32, (*w >> 32) as u32])
+            .collect();
+        BigUint::new(digits) + 1u32
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the direction at index `i`, if there is one.
+    pub fn get(&self, i: usize) -> Option<bool> {
+        if i < self.len {
+            Some(self.words[i / WORD_BITS] >> (i % WORD_BITS) & 1 == 1)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the direction at index `i`.
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn set(&mut self, i: usize, dir: bool) {
+        assert!(i < self.len, "Index out of bounds.");
+        let bit = 1 << (i % WORD_BITS);
+        if dir {
+            self.words[i / WORD_BITS] |= bit;
+        } else {
+            self.words[i / WORD_BITS] &= !bit;
+        }
+    }
+
+    /// Returns the last direction, if there is one.
+    pub fn last(&self) -> Option<bool> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Appends a direction to the end of the path.
+    pub fn push(&mut self, dir: bool) {
+        if self.len % WORD_BITS == 0 {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, dir);
+    }
+
+    /// Removes the last direction and returns it, if there is one.
+    pub fn pop(&mut self) -> Option<bool> {
+        let dir = self.last()?;
+        self.set(self.len - 1, false);
+        self.len -= 1;
+        if self.len % WORD_BITS == 0 {
+            self.words.pop();
+        }
+        Some(dir)
+    }
+
+    /// Appends every direction of another path to the end of this one.
+    pub fn append(&mut self, other: &Path) {
+        let offset = self.len % WORD_BITS;
+        if offset == 0 {
+            self.words.extend_from_slice(&other.words);
+        } else {
+            for w in &other.words {
+                *self.words.last_mut().unwrap() |= w << offset;
+                self.words.push(w >> (WORD_BITS - offset));
+            }
+        }
+        self.len += other.len;
+        self.words.truncate(self.len.div_ceil(WORD_BITS));
+    }
+
+    /// Returns the directions from index `start` (inclusive) to `end` (exclusive) as a new path.
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn slice(&self, start: usize, end: usize) -> Path {
+        assert!(start <= end && end <= self.len, "Range out of bounds.");
+        let len = end - start;
+        let offset = start % WORD_BITS;
+        let first = start / WORD_BITS;
+
+        let mut p = Path {
+            words: (0..len.div_ceil(WORD_BITS))
+                .map(|j| {
+                    let lo = self.words[first + j] >> offset;
+                    let hi = match self.words.get(first + j + 1) {
+                        Some(w) if offset != 0 => w << (WORD_BITS - offset),
+                        _ => 0,
+                    };
+                    lo | hi
+                })
+                .collect(),
+            len,
+        };
+        p.mask();
+        p
+    }
+
+    /// Rotates the path in-place such that the first `k` directions move to the end.
+    ///
+    /// Panics if `k` is greater than the length.
+    pub fn rotate_left(&mut self, k: usize) {
+        assert!(k <= self.len, "Rotation out of bounds.");
+        let mut p = self.slice(k, self.len);
+        p.append(&self.slice(0, k));
+        *self = p;
+    }
+
+    /// Returns the index of the first right direction, if there is one.
+    pub fn first_right(&self) -> Option<usize> {
+        self.words
+            .iter()
+            .position(|w| *w != 0)
+            .map(|i| i * WORD_BITS + self.words[i].trailing_zeros() as usize)
+    }
+
+    /// Iterates over the directions from the start of the path.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |i| self.get(i).unwrap())
+    }
+
+    /// Clears the unused bits of the last word.
+    fn mask(&mut self) {
+        let used = self.len % WORD_BITS;
+        if used != 0 {
+            if let Some(w) = self.words.last_mut() {
+                *w &= (1 << used) - 1;
+            }
+        }
+    }
+}
+
+impl Index<usize> for Path {
+    type Output = bool;
+
+    fn index(&self, i: usize) -> &bool {
+        match self.get(i) {
+            Some(true) => &true,
+            Some(false) => &false,
+            None => panic!("Index out of bounds."),
+        }
+    }
+}
+
+impl FromIterator<bool> for Path {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut p = Path::new();
+        for dir in iter {
+            p.push(dir);
+        }
+        p
+    }
+}
+
+impl fmt::Display for Path {
+    /// Writes the path as a string of `L` and `R` directions.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s: String = self.iter().map(|dir| if dir { 'R' } else { 'L' }).collect();
+        f.write_str(&s)
+    }
+}
+
+impl fmt::Debug for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Path({})", self)
+    }
+}
+
+impl FromStr for Path {
+    type Err = CollatzError;
+
+    /// Parses a string of `L` and `R` directions.
+    fn from_str(s: &str) -> Result<Self, CollatzError> {
+        s.chars()
+            .map(|c| match c {
+                'L' => Ok(false),
+                'R' => Ok(true),
+                _ => Err(CollatzError::Parse),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::ToBigUint;
+    use num_traits::One;
+
+    #[test]
+    fn test_path_number() {
+        struct Case {
+            n: BigUint,
+            p: String,
+        }
+        let cases = vec![
+            // edge
+            Case {
+                n: BigUint::one(),
+                p: String::new(),
+            },
+            Case {
+                n: 2.to_biguint().unwrap(),
+                p: "L".to_string(),
+            },
+            Case {
+                n: 3.to_biguint().unwrap(),
+                p: "RL".to_string(),
+            },
+            Case {
+                n: 12.to_biguint().unwrap(),
+                p: "LLRL".to_string(),
+            },
+            // spans several words
+            Case {
+                n: BigUint::one() << 130u32,
+                p: "L".repeat(130),
+            },
+        ];
+        for case in cases {
+            let p: Path = case.p.parse().unwrap();
+            assert_eq!(Path::of(&case.n), p, "Wrong path from number.");
+            assert_eq!(p.to_n(), case.n, "Wrong number from path.");
+            assert_eq!(p.to_string(), case.p, "Wrong display.");
+        }
+    }
+
+    #[test]
+    fn test_path_ops() {
+        let dirs: Vec<bool> = (0..150).map(|i| i % 3 == 0 || i % 7 == 0).collect();
+        let mut p: Path = dirs.iter().copied().collect();
+        assert_eq!(p.len(), 150);
+        assert_eq!(p.iter().collect::<Vec<_>>(), dirs, "Wrong directions.");
+        assert_eq!(p.first_right(), Some(0));
+
+        for k in [0, 1, 63, 64, 65, 149, 150] {
+            let mut q = p.clone();
+            q.rotate_left(k);
+            let mut rotated = dirs.clone();
+            rotated.rotate_left(k);
+            assert_eq!(
+                q.iter().collect::<Vec<_>>(),
+                rotated,
+                "Wrong rotation by {}.",
+                k
+            );
+        }
+
+        let mut q = p.slice(0, 70);
+        q.append(&p.slice(70, 150));
+        assert_eq!(q, p, "Slices should append back to the original.");
+
+        p.set(149, true);
+        assert_eq!(p.pop(), Some(true));
+        p.push(false);
+        assert_eq!(p.last(), Some(false));
+        assert_eq!(p.len(), 150);
+        assert!(!p[149]);
+
+        assert_eq!(Path::filled(false, 100).first_right(), None);
+        assert_eq!("LLR".parse::<Path>().unwrap().first_right(), Some(2));
+        assert_eq!("LXR".parse::<Path>(), Err(CollatzError::Parse));
+    }
+}
@@ -1,11 +1,49 @@
+use std::fmt;
+
 use num_bigint::BigUint;
-use num_traits::One;
+use num_traits::{One, Zero};
 
 use crate::{
+    path::Path,
     prefix,
-    utils::{from_binary, from_path, is_pow2},
+    utils::{from_path, is_pow2},
+    CollatzError,
 };
 
+/// Nature of a node in PIPTree, which decides how the prefix changes on the way to its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Nature {
+    Good,
+    Bad,
+}
+
+impl Nature {
+    pub fn is_good(self) -> bool {
+        self == Nature::Good
+    }
+}
+
+impl From<bool> for Nature {
+    /// Maps the output of [`find_nature`] to a nature.
+    fn from(good: bool) -> Self {
+        if good {
+            Nature::Good
+        } else {
+            Nature::Bad
+        }
+    }
+}
+
+impl fmt::Display for Nature {
+    /// Writes the nature as `G` or `B`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Nature::Good => "G",
+            Nature::Bad => "B",
+        })
+    }
+}
+
 /// Finds the nature of a path.
 ///
 /// This is done by checking the parity of iterating the number at path `p` with a prefix
@@ -14,99 +52,320 @@ use crate::{
 /// - `true`: result is even, refers to GOOD nature
 /// - `false`: result is odd, refers to BAD nature
 #[inline]
-pub fn find_nature(p: &[bool], pf: &[u32], rpf: u32) -> bool {
+pub fn find_nature(p: &Path, pf: &[u32], rpf: u32) -> bool {
     // check if the result of prefix iteration is even or odd
     !(prefix::iterate(from_path(p), &[pf, &[rpf + 1]].concat()).bit(0))
 }
 
+/// Finds the nature of every node in the PIPTree for paths of length `len`.
+///
+/// The nodes are in heap order: the root is at index 0, and the children of the node at index `i`
+/// are at `2i + 1` (left) and `2i + 2` (right). So there are `2^len - 1` of them, see [`get_root_directions`].
+///
+/// Panics if `len` is zero.
+pub fn natures(len: usize) -> Vec<Nature> {
+    let mut ans = vec![Nature::Good; (1 << len) - 1];
+    let mut cursor = PipCursor::root(len);
+
+    // DFS with the cursor, so that each node is reached by a single step
+    let mut index = 0;
+    loop {
+        ans[index] = cursor.nature();
+        if cursor.go_left() {
+            index = 2 * index + 1;
+            continue;
+        }
+
+        // backtrack to the deepest left turn, then go right instead
+        loop {
+            if index == 0 {
+                return ans;
+            }
+            let left = index % 2 == 1;
+            cursor.go_up();
+            index = (index - 1) / 2;
+            if left {
+                cursor.go_right();
+                index = 2 * index + 2;
+                break;
+            }
+        }
+    }
+}
+
+/// Finds the natures of the nodes on the way from the root to the node at path `p`, as a string of `G` and `B`.
+///
+/// Panics if the path has no right direction, as then it is not in a PIPTree.
+pub fn nature_string(p: &Path) -> String {
+    PipCursor::at(p).nature_string()
+}
+
 /// Finds the path from root to the node indexed by p in PIPTree, with the path length of the root node being equal to |p|.
 ///
-/// It starts from the target, and in a loop either does `m/2` or `(m-1)/2` until it reaches 1.
-/// This gives the path from that number to root, so we reverse that to obtain the road from path to target.
+/// Reading `p` as a binary number `m` with the first direction as the most significant bit,
+/// going from the target to the root would either do `m/2` or `(m-1)/2` until it reaches 1.
+/// Reversed, these are exactly the bits of `m` after its leading one, so they are sliced out of `p`.
 ///
-/// In the resulting array:
+/// In the resulting path:
 /// - `true`: right
 /// - `false`: left
-pub fn get_root_directions(p: &[bool]) -> Vec<bool> {
-    let mut ans = vec![];
-
-    let mut i = from_binary(p);
-    while i > BigUint::one() {
-        if i.bit(0) {
-            i -= BigUint::one();
-            ans.push(true); // right
-        } else {
-            ans.push(false); // left
-        }
-        i >>= 1;
+pub fn get_root_directions(p: &Path) -> Path {
+    match p.first_right() {
+        Some(i) => p.slice(i + 1, p.len()),
+        None => Path::new(),
+    }
+}
+
+/// Finds the path of a number in the PIPTree for paths of length `len`, if it is there.
+///
+/// Appending right directions to a path keeps its number, so the path from [`Path::of`] is padded with them.
+/// The tree has the numbers from 1 to `2^len - 1`, as every other path is either longer or all left.
+pub fn find_path(n: &BigUint, len: usize) -> Option<Path> {
+    if n.is_zero() {
+        return None;
     }
 
-    ans.reverse();
-    ans
+    let mut p = Path::of(n);
+    if p.len() > len {
+        return None;
+    }
+    p.append(&Path::filled(true, len - p.len()));
+    p.first_right().map(|_| p)
 }
 
 /// Finds the prefix of a number using PIPTree properties.
-pub fn prefix_find(n: BigUint, p: &Vec<bool>) -> Vec<u32> {
+pub fn prefix_find(n: BigUint, p: &Path) -> Vec<u32> {
     assert_eq!(from_path(p), n, "Number must be at this path.");
 
     if is_pow2(&n) {
-        let mut nn = n; // TODO: can be removed?
-        let mut ans = 0;
-        while nn > BigUint::one() {
-            nn >>= 1;
-            ans += 1;
-        }
-        vec![ans]
+        // the exponent is the run of even steps to 1
+        vec![n.trailing_zeros().unwrap() as u32]
     } else {
-        let dirs = get_root_directions(p);
+        // start from the root and work your way to the target
+        PipCursor::at(p).into_prefix()
+    }
+}
+
+/// What is lost when going down from a node, so that [`PipCursor::go_up`] can restore it.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    /// The root prefix was appended to the prefix.
+    pushed: bool,
+    /// The bit of the number that was shifted out.
+    n_bit: bool,
+    /// The direction that was rotated out of the path.
+    p_dir: bool,
+    /// Nature of the node that was left.
+    nature: Nature,
+}
+
+/// A cursor over the PIPTree of a given path length, holding the number, path and prefix of the current node.
+///
+/// Going down finds the nature of the current node once, and updates the prefix, number and path from it.
+/// Going up undoes the last step from what was saved while going down, without any nature evaluations.
+///
+/// The prefix of every node is the same as [`prefix_find`].
+#[derive(Debug, Clone)]
+pub struct PipCursor {
+    root_pf: u32,
+    root_n: BigUint,
+    pf: Vec<u32>,
+    n: BigUint,
+    p: Path,
+    steps: Vec<Step>,
+}
 
-        let root_pf = (p.len() - 1) as u32;
+impl PipCursor {
+    /// Creates a cursor at the root of the tree for paths of length `len`.
+    ///
+    /// The root is `2^(len-1)` at the path `L...LR`.
+    ///
+    /// Panics if `len` is zero.
+    pub fn root(len: usize) -> Self {
+        assert!(len > 0, "Path length must be positive.");
+        let root_pf = (len - 1) as u32;
         let root_n = BigUint::one() << root_pf;
-        let root_p = [vec![false; p.len() - 1], vec![true]].concat();
+        let mut p = Path::filled(false, len - 1);
+        p.push(true);
 
-        // start from the root and work your way to the target
-        let mut cur_pf = vec![root_pf];
-        let mut cur_n = root_n.clone();
-        let mut cur_p = root_p;
+        PipCursor {
+            root_pf,
+            root_n: root_n.clone(),
+            pf: vec![root_pf],
+            n: root_n,
+            p,
+            steps: vec![],
+        }
+    }
 
-        for dir in dirs {
-            // nature of current node
-            let nat = find_nature(&cur_p, &cur_pf, root_pf);
+    /// Creates a cursor at the node of a path, walking from the root via [`get_root_directions`].
+    ///
+    /// Panics if the path has no right direction, as then it is not in a PIPTree.
+    pub fn at(p: &Path) -> Self {
+        assert!(
+            p.first_right().is_some(),
+            "Path must have a right direction."
+        );
+        let mut cursor = PipCursor::root(p.len());
+        for dir in get_root_directions(p).iter() {
+            cursor.go(dir);
+        }
+        cursor
+    }
 
-            // decrement everything in the prefix
-            for pf_i in &mut cur_pf {
-                *pf_i -= 1;
-            }
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
 
-            // append root prefix if
-            // BAD and RIGHT, or
-            // GOOD and LEFT
-            if (dir && !nat) || (!dir && nat) {
-                cur_pf.push(root_pf);
-            }
+    pub fn path(&self) -> &Path {
+        &self.p
+    }
+
+    /// Returns the prefix of the current node.
+    ///
+    /// A power of two goes around the cycle at 1 along the walk, so its prefix is cut to the run of even steps,
+    /// as in [`prefix_find`]. The rest is still kept, as the natures further down depend on it.
+    pub fn prefix(&self) -> &[u32] {
+        if is_pow2(&self.n) {
+            &self.pf[..1]
+        } else {
+            &self.pf
+        }
+    }
 
-            // div by 2
-            cur_n >>= 1;
+    pub fn into_prefix(mut self) -> Vec<u32> {
+        let len = self.prefix().len();
+        self.pf.truncate(len);
+        self.pf
+    }
 
-            // if GOOD, add root too
-            if !dir {
-                cur_n += root_n.clone();
-            }
+    /// Number of edges from the root.
+    pub fn depth(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns `true` if the current node has children, i.e. it is above the last level.
+    pub fn has_children(&self) -> bool {
+        self.depth() < self.p.len() - 1
+    }
+
+    /// Finds the nature of the current node, see [`find_nature`].
+    pub fn nature(&self) -> Nature {
+        find_nature(&self.p, &self.pf, self.root_pf).into()
+    }
+
+    /// Returns the natures of the nodes from the root to the current node, as a string of `G` and `B`.
+    ///
+    /// The natures above the current node were found on the way down, so only the last one is evaluated.
+    pub fn nature_string(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| step.nature)
+            .chain(std::iter::once(self.nature()))
+            .map(|nature| nature.to_string())
+            .collect()
+    }
+
+    /// Goes to the left child, returning `false` if there is none.
+    pub fn go_left(&mut self) -> bool {
+        self.go(false)
+    }
+
+    /// Goes to the right child, returning `false` if there is none.
+    pub fn go_right(&mut self) -> bool {
+        self.go(true)
+    }
+
+    /// Goes to the child in the given direction, returning `false` if there is none.
+    pub fn go(&mut self, dir: bool) -> bool {
+        if !self.has_children() {
+            return false;
+        }
+
+        // nature of current node
+        let nature = self.nature();
+
+        // decrement everything in the prefix
+        for pf_i in &mut self.pf {
+            *pf_i -= 1;
+        }
+
+        // append root prefix if
+        // BAD and RIGHT, or
+        // GOOD and LEFT
+        let pushed = dir != nature.is_good();
+        if pushed {
+            self.pf.push(self.root_pf);
+        }
+
+        // div by 2, and if GOOD, add root too
+        let n_bit = self.n.bit(0);
+        self.n >>= 1;
+        if !dir {
+            self.n += &self.root_n;
+        }
+
+        // go to the next child
+        let p_dir = self.p[0];
+        self.p.rotate_left(1);
+        let last = self.p.len() - 1;
+        self.p.set(last, dir);
+
+        self.steps.push(Step {
+            pushed,
+            n_bit,
+            p_dir,
+            nature,
+        });
+        true
+    }
+
+    /// Goes to the parent, returning `false` if this is the root.
+    pub fn go_up(&mut self) -> bool {
+        let step = match self.steps.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+
+        // go back to the previous node
+        let last = self.p.len() - 1;
+        if !self.p[last] {
+            self.n -= &self.root_n;
+        }
+        self.n <<= 1;
+        self.n.set_bit(0, step.n_bit);
 
-            // go to the next child
-            cur_p.rotate_left(1);
-            let last = cur_p.last_mut().unwrap();
-            *last = dir;
+        self.p.set(last, step.p_dir);
+        self.p.rotate_left(last);
+
+        // undo the prefix
+        if step.pushed {
+            self.pf.pop();
+        }
+        for pf_i in &mut self.pf {
+            *pf_i += 1;
         }
+        true
+    }
+}
 
-        cur_pf
+/// Fallible version of [`prefix_find`], rejecting a number that is not at the given path.
+pub fn try_prefix_find(n: BigUint, p: &Path) -> Result<Vec<u32>, CollatzError> {
+    if from_path(p) != n {
+        return Err(CollatzError::PathMismatch);
     }
+
+    Ok(prefix_find(n, p))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::to_path;
+    use crate::{riptree, utils::to_path};
     use num_bigint::ToBigUint;
 
     #[test]
@@ -166,4 +425,252 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_pip_cursor() {
+        let mut cursor = PipCursor::root(6);
+        assert_eq!(*cursor.n(), 32.to_biguint().unwrap());
+        assert_eq!(cursor.path().to_string(), "LLLLLR");
+        assert_eq!(cursor.prefix(), &[5]);
+        assert!(!cursor.go_up(), "Root should have no parent.");
+
+        // walk down and back up along every path of the tree, checking each node
+        let mut visited = 1;
+        let mut dirs = vec![];
+        loop {
+            if cursor.go_left() {
+                dirs.push(false);
+            } else {
+                // backtrack to the deepest left turn, then go right instead
+                while dirs.last() == Some(&true) {
+                    dirs.pop();
+                    assert!(cursor.go_up());
+                }
+                if dirs.pop().is_none() {
+                    break;
+                }
+                assert!(cursor.go_up());
+                assert!(cursor.go_right());
+                dirs.push(true);
+            }
+            visited += 1;
+
+            assert_eq!(cursor.depth(), dirs.len());
+            assert_eq!(
+                *cursor.n(),
+                from_path(cursor.path()),
+                "Wrong number at path."
+            );
+            assert_eq!(
+                get_root_directions(cursor.path())
+                    .iter()
+                    .collect::<Vec<_>>(),
+                dirs,
+                "Wrong path."
+            );
+            assert_eq!(
+                cursor.prefix(),
+                PipCursor::at(cursor.path()).prefix(),
+                "Going up should restore the prefix at {}.",
+                cursor.path()
+            );
+            assert_eq!(
+                cursor.prefix(),
+                prefix_find(cursor.n().clone(), cursor.path()),
+                "Prefix should match prefix_find at {}.",
+                cursor.path()
+            );
+            assert_eq!(
+                cursor.prefix(),
+                riptree::prefix_find(cursor.n().clone(), cursor.path()),
+                "Prefix should match the RIPTree at {}.",
+                cursor.path()
+            );
+        }
+        assert_eq!(
+            visited,
+            (1 << 6) - 1,
+            "Every path with a right direction should be visited."
+        );
+        assert!(cursor.is_root());
+        assert_eq!(cursor.prefix(), &[5]);
+    }
+
+    #[test]
+    fn test_natures() {
+        struct Case {
+            len: usize,
+            natures: &'static str,
+        }
+        let cases = vec![
+            Case {
+                len: 1,
+                natures: "G",
+            },
+            Case {
+                len: 2,
+                natures: "GGB",
+            },
+            Case {
+                len: 3,
+                natures: "GGBGGGG",
+            },
+            Case {
+                len: 4,
+                natures: "GGBGGGGGBGGGBBB",
+            },
+            Case {
+                len: 5,
+                natures: "GGBGGGGGBGGGBBBGGGGBGBBGGGGGGBG",
+            },
+        ];
+        for case in cases {
+            let level: String = natures(case.len).iter().map(Nature::to_string).collect();
+            assert_eq!(level, case.natures, "Wrong natures: {}", case.len);
+        }
+
+        for len in 1..8 {
+            let level = natures(len);
+            assert_eq!(level.len(), (1 << len) - 1);
+
+            for (i, nature) in level.iter().enumerate() {
+                // the heap index of a node, plus one, is its path read as binary with L for 0
+                let p: Path = format!("{:0len$b}", i + 1)
+                    .replace('0', "L")
+                    .replace('1', "R")
+                    .parse()
+                    .unwrap();
+                assert_eq!(
+                    PipCursor::at(&p).nature(),
+                    *nature,
+                    "Wrong nature at {}.",
+                    p
+                );
+
+                // the ancestors of a node in heap order are at (i - 1) / 2, up to the root
+                let mut expected: Vec<String> =
+                    std::iter::successors(Some(i), |j| j.checked_sub(1).map(|j| j / 2))
+                        .map(|j| level[j].to_string())
+                        .collect();
+                expected.reverse();
+                assert_eq!(
+                    nature_string(&p),
+                    expected.concat(),
+                    "Wrong nature string at {}.",
+                    p
+                );
+            }
+        }
+
+        assert_eq!(nature_string(&"LLLR".parse().unwrap()), "G");
+        assert_eq!(nature_string(&"RRRR".parse().unwrap()), "GBGB");
+    }
+
+    #[test]
+    fn test_root_directions() {
+        struct Case {
+            p: &'static str,
+            dirs: &'static str,
+        }
+        let cases = vec![
+            // root
+            Case {
+                p: "LLLR",
+                dirs: "",
+            },
+            Case {
+                p: "LLRR",
+                dirs: "R",
+            },
+            Case {
+                p: "RLRL",
+                dirs: "LRL",
+            },
+        ];
+        for case in cases {
+            assert_eq!(
+                get_root_directions(&case.p.parse().unwrap()),
+                case.dirs.parse().unwrap(),
+                "Wrong directions."
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_path() {
+        struct Case {
+            n: u32,
+            len: usize,
+            p: Option<&'static str>,
+        }
+        let cases = vec![
+            // edge
+            Case {
+                n: 0,
+                len: 4,
+                p: None,
+            },
+            Case {
+                n: 1,
+                len: 4,
+                p: Some("RRRR"),
+            },
+            // root
+            Case {
+                n: 8,
+                len: 4,
+                p: Some("LLLR"),
+            },
+            Case {
+                n: 3,
+                len: 4,
+                p: Some("RLRR"),
+            },
+            Case {
+                n: 15,
+                len: 4,
+                p: Some("RLLL"),
+            },
+            // all left, or too long
+            Case {
+                n: 16,
+                len: 4,
+                p: None,
+            },
+            Case {
+                n: 17,
+                len: 4,
+                p: None,
+            },
+        ];
+        for case in cases {
+            let n = case.n.to_biguint().unwrap();
+            assert_eq!(
+                find_path(&n, case.len),
+                case.p.map(|p| p.parse().unwrap()),
+                "Wrong path of {}.",
+                case.n
+            );
+        }
+
+        // every number in the tree is found at a path of the right length
+        for n in (1..32u32).map(BigUint::from) {
+            let p = find_path(&n, 5).unwrap();
+            assert_eq!(p.len(), 5);
+            assert_eq!(from_path(&p), n, "Wrong number at path.");
+        }
+    }
+
+    #[test]
+    fn test_try_prefix_find() {
+        let n = 27.to_biguint().unwrap();
+        assert_eq!(
+            try_prefix_find(n.clone(), &to_path(&n)),
+            Ok(vec![0, 1, 3, 4])
+        );
+        assert_eq!(
+            try_prefix_find(n.clone(), &to_path(&(n + BigUint::one()))),
+            Err(CollatzError::PathMismatch)
+        );
+    }
 }
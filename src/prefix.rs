@@ -1,24 +1,34 @@
+use std::{fmt, ops::Add, str::FromStr};
+
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 
-use crate::CollatzIteration;
+use crate::{
+    backend::{Backend, Number},
+    collatz,
+    residue::ResidueClass,
+    CollatzError, CollatzIteration,
+};
 
 /// Returns the prefix of two numbers.
 ///
 /// The prefix can be thought of as common prefix of the ECFs.
 /// As an example, `ECF(3) = [0, 1, 5]` and `ECF(7) = [0, 1, 2, 4, 7, 11]`.
 /// The common prefix here is `[0, 1]`, thus `find(3,7) = find(7,3) = [0, 1]`.
-pub fn find(mut n: BigUint, mut m: BigUint) -> Vec<u32> {
+pub fn find(n: BigUint, m: BigUint) -> Vec<u32> {
+    let mut n = Number::from(n);
+    let mut m = Number::from(m);
     let mut ans = vec![];
     let mut twos = 0;
 
     loop {
-        if !n.bit(0) && !m.bit(0) {
-            // both are even
-            twos += 1;
-            n >>= 1;
-            m >>= 1;
-        } else if n.bit(0) && m.bit(0) {
+        if !n.is_odd() && !m.is_odd() {
+            // both are even, jump over the shared run of even steps
+            let k = n.trailing_zeros().min(m.trailing_zeros()).max(1);
+            twos += k as u32;
+            n.shr(k);
+            m.shr(k);
+        } else if n.is_odd() && m.is_odd() {
             // both are odd
             ans.push(twos);
             n.three_x_plus_one();
@@ -31,24 +41,126 @@ pub fn find(mut n: BigUint, mut m: BigUint) -> Vec<u32> {
     ans
 }
 
+/// Returns the prefix of any number of values, and how they split after it.
+///
+/// All values are iterated in lockstep until their parities disagree. At that point,
+/// the indices of the values are grouped by parity, with the group of the first value coming first.
+/// As an example, `find_many(&[3, 7, 11])` is `([0, 1], [[0, 2], [1]])`.
+///
+/// If all values are equal, the prefix is their ECF and there is a single group.
+/// If there are no values, both the prefix and the groups are empty.
+pub fn find_many(ns: &[BigUint]) -> (Vec<u32>, Vec<Vec<usize>>) {
+    let first = match ns.first() {
+        Some(first) => first,
+        None => return (vec![], vec![]),
+    };
+    if ns.iter().all(|n| n == first) {
+        return (collatz::ecf(first.clone()), vec![(0..ns.len()).collect()]);
+    }
+
+    let mut ns: Vec<Number> = ns.iter().cloned().map(Number::from).collect();
+    let mut ans = vec![];
+    let mut twos = 0;
+
+    loop {
+        let odd = ns[0].is_odd();
+        if ns.iter().any(|n| n.is_odd() != odd) {
+            break;
+        }
+
+        if odd {
+            ans.push(twos);
+            ns.iter_mut().for_each(|n| n.three_x_plus_one());
+        } else {
+            // jump over the shared run of even steps
+            let k = ns.iter().map(|n| n.trailing_zeros()).min().unwrap().max(1);
+            twos += k as u32;
+            ns.iter_mut().for_each(|n| n.shr(k));
+        }
+    }
+
+    let (same, other) = (0..ns.len()).partition(|i| ns[*i].is_odd() == ns[0].is_odd());
+    (ans, vec![same, other])
+}
+
 /// Iterates a number through a prefix.
 ///
 /// If the prefix is equal to ECF of the number, the result is expected to be 1.
-pub fn iterate(mut n: BigUint, pf: &Vec<u32>) -> BigUint {
+pub fn iterate(n: BigUint, pf: &[u32]) -> BigUint {
     if pf.is_empty() {
         n
     } else {
+        let mut n = Number::from(n);
+
         // R_0 function
-        n /= BigUint::one() << pf[0];
+        n.shr(pf[0].into());
 
         // R function for i = 1..len(pf)
         for i in 1..pf.len() {
             n.three_x_plus_one();
-            n /= BigUint::one() << (pf[i] - pf[i - 1]);
+            n.shr((pf[i] - pf[i - 1]).into());
         }
 
-        n
+        n.into()
+    }
+}
+
+/// Fallible version of [`iterate`], rejecting prefixes that are not strictly ascending.
+pub fn try_iterate(n: BigUint, pf: &[u32]) -> Result<BigUint, CollatzError> {
+    check_strictly_ascending(pf)?;
+    Ok(iterate(n, pf))
+}
+
+/// Strict version of [`iterate`], checking that the prefix applies to the number.
+///
+/// A prefix applies if it is the start of the ECF, i.e. every division by a power of two is exact
+/// and results in an odd number. Otherwise, the element where this first fails is reported.
+pub fn iterate_strict(n: BigUint, pf: &[u32]) -> Result<BigUint, CollatzError> {
+    check_strictly_ascending(pf)?;
+
+    let mut n = Number::from(n);
+    for i in 0..pf.len() {
+        let k = if i == 0 {
+            pf[0]
+        } else {
+            n.three_x_plus_one();
+            pf[i] - pf[i - 1]
+        };
+
+        // the division is exact and results in an odd number iff there are exactly k trailing zeros
+        if n.bits() == 0 || n.trailing_zeros() != k.into() {
+            return Err(CollatzError::PrefixMismatch { step: i });
+        }
+        n.shr(k.into());
+    }
+
+    Ok(n.into())
+}
+
+/// Traced version of [`iterate`], returning the number after each step, i.e. after each element of the prefix.
+///
+/// The last value is the result of [`iterate`]. Prefixes that are not strictly ascending are rejected.
+pub fn iterate_traced(n: BigUint, pf: &[u32]) -> Result<Vec<BigUint>, CollatzError> {
+    check_strictly_ascending(pf)?;
+    Ok(traced(n, pf))
+}
+
+/// Unchecked version of [`iterate_traced`], for prefixes that are known to be strictly ascending.
+fn traced(n: BigUint, pf: &[u32]) -> Vec<BigUint> {
+    let mut n = Number::from(n);
+    let mut ans = Vec::with_capacity(pf.len());
+
+    for i in 0..pf.len() {
+        if i == 0 {
+            n.shr(pf[0].into());
+        } else {
+            n.three_x_plus_one();
+            n.shr((pf[i] - pf[i - 1]).into());
+        }
+        ans.push(n.to_big());
     }
+
+    ans
 }
 
 /// Bijective mapping from a list of ascending numbers to an integer.
@@ -83,7 +195,7 @@ pub fn from_num(mut k: BigUint) -> Vec<u32> {
 ///+-------------------------
 ///sum: [a, b, x+c, y+c, z+c]
 ///```
-pub fn add(pf1: &Vec<u32>, pf2: &Vec<u32>) -> Vec<u32> {
+pub fn add(pf1: &[u32], pf2: &[u32]) -> Vec<u32> {
     // edge cases
     if pf1.is_empty() {
         return pf2.to_vec();
@@ -104,15 +216,226 @@ pub fn add(pf1: &Vec<u32>, pf2: &Vec<u32>) -> Vec<u32> {
     ans
 }
 
+/// Subtract a prefix from the start of another, i.e. the inverse of [`add`].
+///
+/// Returns the tail such that `add(head, tail) == total`, or `None` if there is none.
+/// When `head == total` the tail is `[]`, although `[0]` would attach to the same result.
+///
+///```md
+///total: [a, b, x+c, y+c, z+c]
+///head:  [a, b, c]
+///+-------------------------
+///tail:        [x,   y,   z]
+///```
+pub fn sub(total: &[u32], head: &[u32]) -> Option<Vec<u32>> {
+    // edge cases
+    if head.is_empty() {
+        return Some(total.to_vec());
+    }
+    if head == total {
+        return Some(vec![]);
+    }
+
+    let k = head.len() - 1;
+    let last = head[k];
+    if total.len() <= k || total[..k] != head[..k] || total[k] < last {
+        return None;
+    }
+
+    let mut ans = vec![total[k] - last];
+    for total_i in total.iter().skip(k + 1) {
+        ans.push(total_i - last);
+    }
+
+    Some(ans)
+}
+
+/// Splits a prefix into a head of `k` elements and a tail, which [`add`] back to the original.
+///
+/// The tail starts with 0 unless it is `[]` or the whole prefix, i.e. `k` is the length or 0.
+///
+/// Panics if `k` is greater than the length.
+pub fn split_at(pf: &[u32], k: usize) -> (Vec<u32>, Vec<u32>) {
+    assert!(k <= pf.len(), "Split out of bounds.");
+    let head = pf[..k].to_vec();
+    let tail = sub(pf, &head).unwrap();
+    (head, tail)
+}
+
+/// Returns `true` if `total` is `head` with some prefix attached to it, see [`sub`].
+///
+/// This is looser than being a prefix of the list, as the last element of the head may grow when attaching.
+pub fn is_head(head: &[u32], total: &[u32]) -> bool {
+    sub(total, head).is_some()
+}
+
+/// A prefix, i.e. a strictly ascending list of numbers.
+///
+/// It is written as a list such as `[0, 1, 5]`, and maps to a number via [`to_num`] & [`from_num`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Prefix(Vec<u32>);
+
+impl Prefix {
+    /// Creates a prefix, rejecting lists that are not strictly ascending.
+    pub fn new(pf: Vec<u32>) -> Result<Self, CollatzError> {
+        check_strictly_ascending(&pf)?;
+        Ok(Prefix(pf))
+    }
+
+    /// Returns the prefix of two numbers, see [`find`].
+    pub fn find(n: BigUint, m: BigUint) -> Self {
+        Prefix(find(n, m))
+    }
+
+    /// Maps a number to a prefix, see [`from_num`].
+    pub fn from_num(k: BigUint) -> Self {
+        Prefix(from_num(k))
+    }
+
+    /// Maps the prefix to a number, see [`to_num`].
+    pub fn to_num(&self) -> BigUint {
+        to_num(self.0.clone())
+    }
+
+    /// Iterates a number through this prefix, see [`iterate`].
+    pub fn iterate(&self, n: BigUint) -> BigUint {
+        iterate(n, &self.0)
+    }
+
+    /// Iterates a number through this prefix, checking that it applies, see [`iterate_strict`].
+    pub fn iterate_strict(&self, n: BigUint) -> Result<BigUint, CollatzError> {
+        iterate_strict(n, &self.0)
+    }
+
+    /// Iterates a number through this prefix, returning the number after each step, see [`iterate_traced`].
+    pub fn iterate_traced(&self, n: BigUint) -> Vec<BigUint> {
+        traced(n, &self.0)
+    }
+
+    /// Subtracts a head from the start of this prefix, see [`sub`].
+    pub fn sub(&self, head: &Prefix) -> Option<Prefix> {
+        sub(&self.0, &head.0).map(Prefix)
+    }
+
+    /// Splits the prefix into a head of `k` elements and a tail, see [`split_at`].
+    pub fn split_at(&self, k: usize) -> (Prefix, Prefix) {
+        let (head, tail) = split_at(&self.0, k);
+        (Prefix(head), Prefix(tail))
+    }
+
+    /// Returns `true` if this prefix is a head of another, see [`is_head`].
+    pub fn is_head_of(&self, total: &Prefix) -> bool {
+        is_head(&self.0, &total.0)
+    }
+
+    /// Returns the class of numbers whose ECF begins with this prefix, see [`ResidueClass`].
+    pub fn residue_class(&self) -> ResidueClass {
+        // a prefix is always strictly ascending
+        ResidueClass::new(&self.0).unwrap()
+    }
+
+    pub fn as_slice(&self) -> &[u32] {
+        &self.0
+    }
+
+    pub fn into_vec(self) -> Vec<u32> {
+        self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl TryFrom<Vec<u32>> for Prefix {
+    type Error = CollatzError;
+
+    fn try_from(pf: Vec<u32>) -> Result<Self, CollatzError> {
+        Prefix::new(pf)
+    }
+}
+
+impl From<Prefix> for Vec<u32> {
+    fn from(pf: Prefix) -> Self {
+        pf.0
+    }
+}
+
+impl AsRef<[u32]> for Prefix {
+    fn as_ref(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl Add for &Prefix {
+    type Output = Prefix;
+
+    /// Attaching preserves strict ascension, see [`add`].
+    fn add(self, other: &Prefix) -> Prefix {
+        Prefix(add(&self.0, &other.0))
+    }
+}
+
+impl Add for Prefix {
+    type Output = Prefix;
+
+    fn add(self, other: Prefix) -> Prefix {
+        &self + &other
+    }
+}
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl FromStr for Prefix {
+    type Err = CollatzError;
+
+    fn from_str(s: &str) -> Result<Self, CollatzError> {
+        Prefix::new(parse_list(s)?)
+    }
+}
+
+/// Returns an error if the list is not strictly ascending.
+pub(crate) fn check_strictly_ascending(pf: &[u32]) -> Result<(), CollatzError> {
+    match (1..pf.len()).find(|&i| pf[i] <= pf[i - 1]) {
+        Some(i) => Err(CollatzError::NotAscending { index: i }),
+        None => Ok(()),
+    }
+}
+
+/// Parses a list of numbers written as `[a, b, c]`.
+pub(crate) fn parse_list(s: &str) -> Result<Vec<u32>, CollatzError> {
+    let inner = s
+        .trim()
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .ok_or(CollatzError::Parse)?;
+
+    if inner.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    inner
+        .split(',')
+        .map(|x| x.trim().parse::<u32>().map_err(|_| CollatzError::Parse))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::collatz;
     use num_bigint::ToBigUint;
     use std::cmp::min;
 
     /// finds the prefix by simply comparing ECFs.
-    fn prefix_brute(a: &Vec<u32>, b: &Vec<u32>) -> Vec<u32> {
+    fn prefix_brute(a: &[u32], b: &[u32]) -> Vec<u32> {
         let minlen = min(a.len(), b.len());
 
         let mut ans = vec![];
@@ -124,7 +447,7 @@ mod tests {
             }
         }
 
-        return ans;
+        ans
     }
 
     #[test]
@@ -150,6 +473,11 @@ mod tests {
                 n: 27.to_biguint().unwrap(),
                 m: 37.to_biguint().unwrap(),
             },
+            // across backend widths
+            Case {
+                n: u64::MAX.to_biguint().unwrap(),
+                m: (BigUint::one() << 200) - 1u32,
+            },
         ];
         for case in cases {
             let ecf_n = collatz::ecf(case.n.clone());
@@ -177,6 +505,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_prefix_find_many() {
+        let to_biguints = |nums: &[u32]| -> Vec<BigUint> {
+            nums.iter().map(|x| x.to_biguint().unwrap()).collect()
+        };
+
+        assert_eq!(
+            find_many(&to_biguints(&[3, 7, 11])),
+            (vec![0, 1], vec![vec![0, 2], vec![1]])
+        );
+        assert_eq!(
+            find_many(&to_biguints(&[12, 3])),
+            (vec![], vec![vec![0], vec![1]])
+        );
+        assert_eq!(
+            find_many(&to_biguints(&[3, 3])),
+            (vec![0, 1, 5], vec![vec![0, 1]]),
+            "Prefix of equal values should be their ECF."
+        );
+        assert_eq!(find_many(&[]), (vec![], vec![]));
+
+        for (n, m) in [(1u32, 2u32), (3, 12), (27, 31), (8, 16), (100, 1000)] {
+            let (n, m) = (n.to_biguint().unwrap(), m.to_biguint().unwrap());
+            assert_eq!(
+                find_many(&[n.clone(), m.clone()]).0,
+                find(n, m),
+                "Prefix of two values should match."
+            );
+        }
+
+        let ns: Vec<BigUint> = (0..10u32)
+            .map(|i| BigUint::from(27u32 + (i << 12)))
+            .collect();
+        let ecfs: Vec<Vec<u32>> = ns.iter().cloned().map(collatz::ecf).collect();
+        let (pf, groups) = find_many(&ns);
+        assert!(
+            ecfs.iter().all(|ecf| ecf.starts_with(&pf)),
+            "Prefix should be shared by all values."
+        );
+        assert_eq!(groups.iter().map(|g| g.len()).sum::<usize>(), ns.len());
+    }
+
+    #[test]
+    fn test_prefix_try_iterate() {
+        assert_eq!(
+            try_iterate(3.to_biguint().unwrap(), &[0, 5, 1]),
+            Err(CollatzError::NotAscending { index: 2 })
+        );
+        assert_eq!(
+            try_iterate(3.to_biguint().unwrap(), &[0, 1, 1, 5]),
+            Err(CollatzError::NotAscending { index: 2 })
+        );
+        assert_eq!(
+            try_iterate(3.to_biguint().unwrap(), &[0, 1, 5]),
+            Ok(BigUint::one())
+        );
+    }
+
     #[test]
     fn test_prefix_add() {
         struct Case {
@@ -218,6 +604,208 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_prefix_iterate_strict() {
+        struct Case {
+            n: BigUint,
+            pf: Vec<u32>,
+            ans: Result<BigUint, CollatzError>,
+        }
+        let cases = vec![
+            // edge
+            Case {
+                n: 3.to_biguint().unwrap(),
+                pf: vec![],
+                ans: Ok(3.to_biguint().unwrap()),
+            },
+            Case {
+                n: BigUint::zero(),
+                pf: vec![0],
+                ans: Err(CollatzError::PrefixMismatch { step: 0 }),
+            },
+            // ECF and its heads
+            Case {
+                n: 3.to_biguint().unwrap(),
+                pf: vec![0, 1, 5],
+                ans: Ok(BigUint::one()),
+            },
+            Case {
+                n: 12.to_biguint().unwrap(),
+                pf: vec![2, 3],
+                ans: Ok(5.to_biguint().unwrap()),
+            },
+            // division is not exact
+            Case {
+                n: 12.to_biguint().unwrap(),
+                pf: vec![3],
+                ans: Err(CollatzError::PrefixMismatch { step: 0 }),
+            },
+            // division is exact, but the result is even
+            Case {
+                n: 3.to_biguint().unwrap(),
+                pf: vec![0, 1, 3],
+                ans: Err(CollatzError::PrefixMismatch { step: 2 }),
+            },
+            Case {
+                n: 3.to_biguint().unwrap(),
+                pf: vec![0, 2],
+                ans: Err(CollatzError::PrefixMismatch { step: 1 }),
+            },
+            // not strictly ascending
+            Case {
+                n: 3.to_biguint().unwrap(),
+                pf: vec![0, 1, 1],
+                ans: Err(CollatzError::NotAscending { index: 2 }),
+            },
+        ];
+        for case in cases {
+            assert_eq!(
+                iterate_strict(case.n.clone(), &case.pf),
+                case.ans,
+                "Wrong strict iteration of {} over {:?}.",
+                case.n,
+                case.pf
+            );
+        }
+
+        for n in (1..1000u32).map(BigUint::from) {
+            let ecf = collatz::ecf(n.clone());
+            assert_eq!(iterate_strict(n.clone(), &ecf), Ok(BigUint::one()));
+            assert_eq!(
+                iterate_traced(n.clone(), &ecf),
+                Ok(collatz::reduced_sequence(n.clone())
+                    .into_iter()
+                    .filter(|x| x.bit(0))
+                    .collect::<Vec<_>>()),
+                "Traced values should be the odd numbers in the sequence."
+            );
+        }
+        assert_eq!(
+            iterate_traced(12.to_biguint().unwrap(), &[2, 3, 5])
+                .unwrap()
+                .last(),
+            Some(&iterate(12.to_biguint().unwrap(), &[2, 3, 5]))
+        );
+        assert_eq!(iterate_traced(12.to_biguint().unwrap(), &[]), Ok(vec![]));
+        assert_eq!(
+            iterate_traced(12.to_biguint().unwrap(), &[3, 1]),
+            Err(CollatzError::NotAscending { index: 1 })
+        );
+        assert_eq!(
+            iterate_traced(12.to_biguint().unwrap(), &[2, 2]),
+            Err(CollatzError::NotAscending { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_prefix_sub() {
+        struct Case {
+            total: Vec<u32>,
+            head: Vec<u32>,
+            tail: Option<Vec<u32>>,
+        }
+        let cases = vec![
+            // edge
+            Case {
+                total: vec![0, 1, 5],
+                head: vec![],
+                tail: Some(vec![0, 1, 5]),
+            },
+            Case {
+                total: vec![0, 1, 5],
+                head: vec![0, 1, 5],
+                tail: Some(vec![]),
+            },
+            Case {
+                total: vec![],
+                head: vec![0],
+                tail: None,
+            },
+            // last element of head grows
+            Case {
+                total: vec![0, 1, 5, 6, 8],
+                head: vec![0, 1, 5],
+                tail: Some(vec![0, 1, 3]),
+            },
+            Case {
+                total: vec![0, 1, 5],
+                head: vec![0, 1, 3],
+                tail: Some(vec![2]),
+            },
+            // not a head
+            Case {
+                total: vec![0, 1, 5],
+                head: vec![0, 2],
+                tail: None,
+            },
+            Case {
+                total: vec![0, 3, 5],
+                head: vec![0, 4],
+                tail: None,
+            },
+        ];
+        for case in cases {
+            assert_eq!(sub(&case.total, &case.head), case.tail, "Wrong tail.");
+            assert_eq!(is_head(&case.head, &case.total), case.tail.is_some());
+            if let Some(tail) = case.tail {
+                assert_eq!(add(&case.head, &tail), case.total, "Tail should add back.");
+            }
+        }
+
+        let pf = collatz::ecf(27.to_biguint().unwrap());
+        for k in 0..=pf.len() {
+            let (head, tail) = split_at(&pf, k);
+            assert_eq!(head.len(), k);
+            assert_eq!(add(&head, &tail), pf, "Split should add back.");
+            assert_eq!(
+                iterate(iterate(27.to_biguint().unwrap(), &head), &tail),
+                BigUint::one(),
+                "Iterating over head then tail should result in 1."
+            );
+        }
+        assert_eq!(split_at(&pf, 0), (vec![], pf.clone()));
+        assert_eq!(split_at(&pf, pf.len()), (pf.clone(), vec![]));
+    }
+
+    #[test]
+    fn test_prefix_type() {
+        assert_eq!(
+            Prefix::new(vec![0, 1, 1]),
+            Err(CollatzError::NotAscending { index: 2 })
+        );
+        assert_eq!(
+            "[0, 2, 1]".parse::<Prefix>(),
+            Err(CollatzError::NotAscending { index: 2 })
+        );
+        assert_eq!("0, 1".parse::<Prefix>(), Err(CollatzError::Parse));
+        assert_eq!("[0, x]".parse::<Prefix>(), Err(CollatzError::Parse));
+        assert_eq!("[]".parse::<Prefix>(), Ok(Prefix::default()));
+
+        let pf1: Prefix = "[0,1,5]".parse().unwrap();
+        let pf2: Prefix = " [0, 1, 3] ".parse().unwrap();
+        assert_eq!(pf1.to_string(), "[0, 1, 5]", "Wrong display.");
+        assert_eq!((&pf1 + &pf2).as_slice(), &[0, 1, 5, 6, 8], "Wrong sum.");
+        assert!(pf2 < pf1, "Prefixes should be ordered lexicographically.");
+        assert_eq!(
+            Prefix::from_num(pf1.to_num()),
+            pf1,
+            "Mapping should be bijective."
+        );
+        assert_eq!(
+            pf1.iterate(3.to_biguint().unwrap()),
+            BigUint::one(),
+            "Iterating over ECF should result in 1."
+        );
+        assert_eq!((&pf1 + &pf2).sub(&pf1), Some(pf2.clone()));
+        assert!(pf1.is_head_of(&(&pf1 + &pf2)));
+        assert_eq!(pf1.split_at(2).0.as_slice(), &[0, 1]);
+        assert_eq!(
+            pf2.residue_class().smallest(),
+            11.to_biguint().unwrap(),
+            "Wrong smallest number with prefix."
+        );
+    }
+
     #[test]
     fn test_prefix_map() {
         struct Case {
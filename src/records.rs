@@ -0,0 +1,187 @@
+use std::iter::FusedIterator;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::collatz;
+
+/// A number that sets a record, along with the value it sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record<V> {
+    pub n: BigUint,
+    pub value: V,
+}
+
+/// Streams the numbers in a range whose value is greater than that of every number before them.
+///
+/// Numbers are visited in order, so a record is yielded as soon as it is found.
+/// Without a starting record, the first number in the range is always a record.
+#[derive(Debug, Clone)]
+pub struct Records<V> {
+    next: BigUint,
+    end: BigUint,
+    best: Option<V>,
+    value: fn(BigUint) -> V,
+}
+
+impl<V: Ord + Clone> Records<V> {
+    /// Creates a scan over `[start, end]` for records of `value`.
+    ///
+    /// Panics if `start` is zero.
+    pub fn new(start: BigUint, end: BigUint, value: fn(BigUint) -> V) -> Self {
+        assert!(!start.is_zero(), "Range must start from 1.");
+        Records {
+            next: start,
+            end,
+            best: None,
+            value,
+        }
+    }
+
+    /// Resumes a scan right after a known record, up to `end`.
+    ///
+    /// Only numbers that beat the given record are yielded.
+    pub fn resume(last: Record<V>, end: BigUint, value: fn(BigUint) -> V) -> Self {
+        Records {
+            next: last.n + 1u32,
+            end,
+            best: Some(last.value),
+            value,
+        }
+    }
+
+    /// Returns the last record found, if there is one.
+    pub fn best(&self) -> Option<&V> {
+        self.best.as_ref()
+    }
+}
+
+impl<V: Ord + Clone> Iterator for Records<V> {
+    type Item = Record<V>;
+
+    fn next(&mut self) -> Option<Record<V>> {
+        while self.next <= self.end {
+            let n = self.next.clone();
+            self.next += 1u32;
+
+            let value = (self.value)(n.clone());
+            if self.best.as_ref().map_or(true, |best| value > *best) {
+                self.best = Some(value.clone());
+                return Some(Record { n, value });
+            }
+        }
+        None
+    }
+}
+
+impl<V: Ord + Clone> FusedIterator for Records<V> {}
+
+/// Delay records in `[start, end]`, i.e. numbers with a longer Collatz length than any before them.
+///
+/// Starting from 1, these are <https://oeis.org/A006877>. See [`collatz::length`].
+pub fn delay(start: BigUint, end: BigUint) -> Records<usize> {
+    Records::new(start, end, collatz::length)
+}
+
+/// Path-height records in `[start, end]`, i.e. numbers that reach a higher peak than any before them.
+///
+/// Starting from 1, these are <https://oeis.org/A006884>. See [`collatz::height`].
+pub fn height(start: BigUint, end: BigUint) -> Records<BigUint> {
+    Records::new(start, end, collatz::height)
+}
+
+/// ECF-length records in `[start, end]`, i.e. numbers with a longer ECF than any before them.
+///
+/// See [`collatz::ecf`] and [`ecf_length`].
+pub fn ecf(start: BigUint, end: BigUint) -> Records<usize> {
+    Records::new(start, end, ecf_length)
+}
+
+/// Length of the ECF of a number, which is the number of odd terms in its Collatz Sequence.
+pub fn ecf_length(n: BigUint) -> usize {
+    collatz::EcfIter::new(n).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::ToBigUint;
+    use num_traits::One;
+
+    /// Collects the numbers of records found in `[1, end]`.
+    fn record_numbers<V: Ord + Clone>(records: Records<V>) -> Vec<u32> {
+        records.map(|r| r.n.try_into().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_records() {
+        let end = 10000.to_biguint().unwrap();
+        assert_eq!(
+            record_numbers(delay(BigUint::one(), end.clone())),
+            vec![
+                1, 2, 3, 6, 7, 9, 18, 25, 27, 54, 73, 97, 129, 171, 231, 313, 327, 649, 703, 871,
+                1161, 2223, 2463, 2919, 3711, 6171
+            ],
+            "Wrong delay records."
+        );
+        assert_eq!(
+            record_numbers(height(BigUint::one(), end.clone())),
+            vec![1, 2, 3, 7, 15, 27, 255, 447, 639, 703, 1819, 4255, 4591, 9663],
+            "Wrong height records."
+        );
+
+        assert_eq!(
+            record_numbers(ecf(BigUint::one(), end.clone())),
+            vec![
+                1, 3, 7, 9, 25, 27, 73, 97, 129, 171, 231, 313, 327, 703, 871, 1161, 2463, 2919,
+                3711, 6171
+            ],
+            "Wrong ECF records."
+        );
+
+        // brute-force scan over the same range
+        let mut expected = vec![];
+        for n in 1..=10000u32 {
+            let value = collatz::ecf(BigUint::from(n)).len();
+            if expected
+                .last()
+                .map_or(true, |r: &Record<usize>| value > r.value)
+            {
+                expected.push(Record {
+                    n: BigUint::from(n),
+                    value,
+                });
+            }
+        }
+        assert_eq!(
+            ecf(BigUint::one(), end).collect::<Vec<_>>(),
+            expected,
+            "ECF records should match a brute-force scan."
+        );
+    }
+
+    #[test]
+    fn test_records_resume() {
+        let end = 10000.to_biguint().unwrap();
+        let all: Vec<Record<usize>> = delay(BigUint::one(), end.clone()).collect();
+        let resumed: Vec<Record<usize>> =
+            Records::resume(all[10].clone(), end.clone(), collatz::length).collect();
+        assert_eq!(
+            resumed,
+            all[11..],
+            "Resumed scan should find the same records."
+        );
+
+        // without a starting record, the first number is a record
+        let mut records = delay(100.to_biguint().unwrap(), end);
+        assert_eq!(
+            records.next().map(|r| r.n),
+            100.to_biguint(),
+            "First number should be a record."
+        );
+        assert_eq!(
+            records.best(),
+            Some(&collatz::length(100.to_biguint().unwrap()))
+        );
+    }
+}
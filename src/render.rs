@@ -0,0 +1,911 @@
+use std::{collections::VecDeque, fmt::Write};
+
+use num_bigint::BigUint;
+
+use crate::{
+    path::Path,
+    piptree::{get_root_directions, Nature, PipCursor},
+    riptree::RipNode,
+};
+
+/// Colour of the edges to left children.
+pub const LEFT_COLOR: &str = "#1f77b4";
+
+/// Colour of the edges to right children.
+pub const RIGHT_COLOR: &str = "#d62728";
+
+/// A part of a node label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Label {
+    Number,
+    Path,
+    Prefix,
+    /// Only PIPTree nodes have a nature, so this is left out for RIPTree nodes.
+    Nature,
+}
+
+/// Labels used by the CLI: the number and its prefix.
+pub const DEFAULT_LABELS: &[Label] = &[Label::Number, Label::Prefix];
+
+/// Colour of the highlighted nodes and edges in SVG.
+pub const HIGHLIGHT_COLOR: &str = "#ff7f0e";
+
+/// Width of a character of the monospace font in SVG, along with its size and line height.
+const CHAR_WIDTH: f64 = 7.2;
+const FONT_SIZE: f64 = 12.0;
+const LINE_HEIGHT: f64 = 15.0;
+
+/// Padding inside the node boxes, and gaps between them in SVG.
+const PADDING: f64 = 6.0;
+const H_GAP: f64 = 12.0;
+const V_GAP: f64 = 36.0;
+
+/// Options of [`Tree::to_svg`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgOptions {
+    pub labels: Vec<Label>,
+    /// Nodes whose subtrees are hidden, drawn with dashed boxes and the number of hidden nodes.
+    pub collapsed: Vec<Path>,
+    /// Node whose route from the root is highlighted, see [`Tree::route`].
+    pub highlight: Option<Path>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            labels: DEFAULT_LABELS.to_vec(),
+            collapsed: vec![],
+            highlight: None,
+        }
+    }
+}
+
+/// A node of a [`Tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeNode {
+    pub n: BigUint,
+    pub path: Path,
+    pub prefix: Vec<u32>,
+    pub nature: Option<Nature>,
+    /// Index of the parent, and the direction taken from it.
+    pub parent: Option<(usize, bool)>,
+    /// Indices of the children, left first.
+    pub children: Vec<usize>,
+    /// The node has children that were left out of the tree, as they are off the route to a focus.
+    pub hidden: bool,
+}
+
+impl TreeNode {
+    /// Writes the given parts of the label, in order.
+    pub fn label(&self, labels: &[Label]) -> Vec<String> {
+        labels
+            .iter()
+            .filter_map(|label| match label {
+                Label::Number => Some(self.n.to_string()),
+                Label::Path => Some(self.path.to_string()),
+                Label::Prefix => Some(format!("{:?}", self.prefix)),
+                Label::Nature => self.nature.map(|nature| nature.to_string()),
+            })
+            .collect()
+    }
+}
+
+/// Which prefix tree a [`Tree`] is taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Rip,
+    Pip,
+}
+
+/// A snapshot of a part of RIPTree or PIPTree, which the renderers draw.
+///
+/// Nodes are stored in BFS order, so the root is at index 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree {
+    kind: Kind,
+    nodes: Vec<TreeNode>,
+}
+
+impl Tree {
+    /// Takes the RIPTree below a node, down to `depth` levels below it.
+    pub fn riptree(root: &RipNode, depth: usize) -> Self {
+        Tree::build(
+            Kind::Rip,
+            root.clone(),
+            depth,
+            rip_snapshot,
+            rip_children,
+            |_| true,
+            |_| true,
+        )
+    }
+
+    /// Takes the RIPTree below a node towards the node at a path, down to `depth` levels below it.
+    ///
+    /// Only the nodes on the [`route`](Tree::route) to the focus have their children taken,
+    /// so the size of the tree grows with the depth rather than exponentially.
+    pub fn riptree_towards(root: &RipNode, depth: usize, focus: &Path) -> Self {
+        Tree::build(
+            Kind::Rip,
+            root.clone(),
+            depth,
+            rip_snapshot,
+            rip_children,
+            |_| true,
+            |node| {
+                let p = node.path();
+                p.len() <= focus.len() && focus.slice(0, p.len()) == *p
+            },
+        )
+    }
+
+    /// Takes the PIPTree below the node of a cursor, down to `depth` levels below it or to the last level.
+    pub fn piptree(root: &PipCursor, depth: usize) -> Self {
+        Tree::build(
+            Kind::Pip,
+            root.clone(),
+            depth,
+            pip_snapshot,
+            pip_children,
+            PipCursor::has_children,
+            |_| true,
+        )
+    }
+
+    /// Takes the PIPTree below the node of a cursor towards the node at a path, down to `depth` levels below it or to the last level.
+    ///
+    /// Only the nodes on the [`route`](Tree::route) to the focus have their children taken,
+    /// so the size of the tree grows with the depth rather than exponentially.
+    pub fn piptree_towards(root: &PipCursor, depth: usize, focus: &Path) -> Self {
+        let focus_dirs = get_root_directions(focus);
+        Tree::build(
+            Kind::Pip,
+            root.clone(),
+            depth,
+            pip_snapshot,
+            pip_children,
+            PipCursor::has_children,
+            |cursor| {
+                let dirs = get_root_directions(cursor.path());
+                cursor.path().len() == focus.len()
+                    && dirs.len() <= focus_dirs.len()
+                    && focus_dirs.slice(0, dirs.len()) == dirs
+            },
+        )
+    }
+
+    /// Builds a tree in BFS order, from a snapshot of each node and its children.
+    ///
+    /// Only the nodes for which `expand` holds have their children taken,
+    /// the others are marked as hidden if `has_children` holds for them.
+    fn build<T>(
+        kind: Kind,
+        root: T,
+        depth: usize,
+        snapshot: impl Fn(&T) -> TreeNode,
+        children: impl Fn(&T) -> Vec<(bool, T)>,
+        has_children: impl Fn(&T) -> bool,
+        expand: impl Fn(&T) -> bool,
+    ) -> Self {
+        let mut nodes: Vec<TreeNode> = vec![];
+        let mut queue = VecDeque::from([(root, 0, None::<(usize, bool)>)]);
+
+        while let Some((cur, level, parent)) = queue.pop_front() {
+            let index = nodes.len();
+            if let Some((p, _)) = parent {
+                nodes[p].children.push(index);
+            }
+            nodes.push(TreeNode {
+                parent,
+                ..snapshot(&cur)
+            });
+
+            if level < depth {
+                if expand(&cur) {
+                    for (dir, child) in children(&cur) {
+                        queue.push_back((child, level + 1, Some((index, dir))));
+                    }
+                } else {
+                    nodes[index].hidden = has_children(&cur);
+                }
+            }
+        }
+
+        Tree { kind, nodes }
+    }
+
+    /// Name of the tree, `riptree` or `piptree`.
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            Kind::Rip => "riptree",
+            Kind::Pip => "piptree",
+        }
+    }
+
+    pub fn nodes(&self) -> &[TreeNode] {
+        &self.nodes
+    }
+
+    pub fn root(&self) -> &TreeNode {
+        &self.nodes[0]
+    }
+
+    /// Finds the index of the node at a path, if it is in the tree.
+    pub fn find(&self, p: &Path) -> Option<usize> {
+        self.nodes.iter().position(|node| node.path == *p)
+    }
+
+    /// Finds the nodes on the way from the root of the tree towards the node at a path, as far as the tree goes.
+    ///
+    /// In RIPTree the directions are the path itself, and in PIPTree they are given by [`get_root_directions`].
+    /// The result is empty if the node is not below the root of the tree.
+    pub fn route(&self, p: &Path) -> Vec<usize> {
+        let directions = |p: &Path| match self.kind {
+            Kind::Rip => p.clone(),
+            Kind::Pip => get_root_directions(p),
+        };
+        let (dirs, root_dirs) = (directions(p), directions(&self.root().path));
+        if (self.kind == Kind::Pip && p.len() != self.root().path.len())
+            || dirs.len() < root_dirs.len()
+            || dirs.slice(0, root_dirs.len()) != root_dirs
+        {
+            return vec![];
+        }
+
+        let mut ans = vec![0];
+        for dir in dirs.slice(root_dirs.len(), dirs.len()).iter() {
+            let cur = *ans.last().unwrap();
+            let next = self.nodes[cur]
+                .children
+                .iter()
+                .find(|c| self.nodes[**c].parent == Some((cur, dir)));
+            match next {
+                Some(child) => ans.push(*child),
+                None => break,
+            }
+        }
+        ans
+    }
+
+    /// Writes the tree in the DOT language of Graphviz.
+    pub fn to_dot(&self, labels: &[Label]) -> String {
+        let mut ans = String::new();
+        writeln!(ans, "digraph {} {{", self.name()).unwrap();
+        writeln!(ans, "  node [shape=box];").unwrap();
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = node.label(labels).join("\\n");
+            writeln!(ans, "  n{} [label=\"{}\"];", i, escape_dot(&label)).unwrap();
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            if let Some((parent, dir)) = node.parent {
+                writeln!(
+                    ans,
+                    "  n{} -> n{} [color=\"{}\", label=\"{}\"];",
+                    parent,
+                    i,
+                    edge_color(dir),
+                    edge_label(dir)
+                )
+                .unwrap();
+            }
+        }
+
+        ans.push_str("}\n");
+        ans
+    }
+
+    /// Writes the tree in GraphML, with the label of each node and the direction and colour of each edge.
+    pub fn to_graphml(&self, labels: &[Label]) -> String {
+        let mut ans = String::new();
+        ans.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        ans.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        ans.push_str(
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        );
+        ans.push_str(
+            "  <key id=\"direction\" for=\"edge\" attr.name=\"direction\" attr.type=\"string\"/>\n",
+        );
+        ans.push_str(
+            "  <key id=\"color\" for=\"edge\" attr.name=\"color\" attr.type=\"string\"/>\n",
+        );
+        writeln!(
+            ans,
+            "  <graph id=\"{}\" edgedefault=\"directed\">",
+            self.name()
+        )
+        .unwrap();
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = node.label(labels).join("\n");
+            writeln!(
+                ans,
+                "    <node id=\"n{}\"><data key=\"label\">{}</data></node>",
+                i,
+                escape_xml(&label)
+            )
+            .unwrap();
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            if let Some((parent, dir)) = node.parent {
+                writeln!(
+                    ans,
+                    "    <edge source=\"n{}\" target=\"n{}\"><data key=\"direction\">{}</data><data key=\"color\">{}</data></edge>",
+                    parent,
+                    i,
+                    edge_label(dir),
+                    edge_color(dir)
+                )
+                .unwrap();
+            }
+        }
+
+        ans.push_str("  </graph>\n</graphml>\n");
+        ans
+    }
+}
+
+impl Tree {
+    /// Draws the tree as a self-contained SVG image, with a tidy layout.
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let collapsed: Vec<bool> = self
+            .nodes
+            .iter()
+            .map(|node| options.collapsed.contains(&node.path))
+            .collect();
+        let highlighted = match &options.highlight {
+            Some(p) => self.route(p),
+            None => vec![],
+        };
+        let positions = self.layout(&collapsed);
+
+        let labels: Vec<Vec<String>> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let mut label = node.label(&options.labels);
+                if collapsed[i] {
+                    label.push(format!("+{}", self.subtree_size(i) - 1));
+                }
+                label
+            })
+            .collect();
+        let lines = labels.iter().map(|l| l.len()).max().unwrap_or(0).max(1);
+        let chars = labels
+            .iter()
+            .flatten()
+            .map(|s| s.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let box_w = chars as f64 * CHAR_WIDTH + 2.0 * PADDING;
+        let box_h = lines as f64 * LINE_HEIGHT + 2.0 * PADDING;
+        let unit_x = box_w + H_GAP;
+        let unit_y = box_h + V_GAP;
+
+        // centre of the box of a node
+        let center = |i: usize| {
+            let (x, depth) = positions[i].unwrap();
+            (
+                H_GAP + box_w / 2.0 + x * unit_x,
+                V_GAP / 2.0 + box_h / 2.0 + depth as f64 * unit_y,
+            )
+        };
+        let visible: Vec<usize> = (0..self.nodes.len())
+            .filter(|i| positions[*i].is_some())
+            .collect();
+        let width = visible
+            .iter()
+            .map(|i| center(*i).0 + box_w / 2.0 + H_GAP)
+            .fold(0.0, f64::max);
+        let height = visible
+            .iter()
+            .map(|i| center(*i).1 + box_h / 2.0 + V_GAP / 2.0)
+            .fold(0.0, f64::max);
+
+        let mut ans = String::new();
+        writeln!(
+            ans,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.1}\" height=\"{h:.1}\" viewBox=\"0 0 {w:.1} {h:.1}\" font-family=\"monospace\" font-size=\"{}\">",
+            FONT_SIZE,
+            w = width,
+            h = height
+        )
+        .unwrap();
+
+        // edges are drawn first, so that the boxes are on top of them
+        for &i in &visible {
+            if let Some((parent, dir)) = self.nodes[i].parent {
+                let ((x1, y1), (x2, y2)) = (center(parent), center(i));
+                let bold = highlighted.contains(&parent) && highlighted.contains(&i);
+                writeln!(
+                    ans,
+                    "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                    x1,
+                    y1 + box_h / 2.0,
+                    x2,
+                    y2 - box_h / 2.0,
+                    edge_color(dir),
+                    if bold { 3 } else { 1 }
+                )
+                .unwrap();
+            }
+        }
+
+        for &i in &visible {
+            let (x, y) = center(i);
+            let stroke = if highlighted.contains(&i) {
+                HIGHLIGHT_COLOR
+            } else {
+                "black"
+            };
+            let dash = if collapsed[i] {
+                " stroke-dasharray=\"4 2\""
+            } else {
+                ""
+            };
+            writeln!(
+                ans,
+                "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"4\" fill=\"white\" stroke=\"{}\" stroke-width=\"{}\"{}/>",
+                x - box_w / 2.0,
+                y - box_h / 2.0,
+                box_w,
+                box_h,
+                stroke,
+                if highlighted.contains(&i) { 3 } else { 1 },
+                dash
+            )
+            .unwrap();
+
+            let top = y - box_h / 2.0 + PADDING + FONT_SIZE;
+            for (j, line) in labels[i].iter().enumerate() {
+                writeln!(
+                    ans,
+                    "  <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+                    x,
+                    top + j as f64 * LINE_HEIGHT,
+                    escape_xml(line)
+                )
+                .unwrap();
+            }
+        }
+
+        ans.push_str("</svg>\n");
+        ans
+    }
+
+    /// Places the nodes in a tidy layout, returning the horizontal position and depth of each visible node.
+    ///
+    /// Following Reingold & Tilford, the subtrees of a node are placed side by side as close as their
+    /// contours allow, and the node is centred above its first and last child. Positions are in units of
+    /// a node box, starting from 0. Nodes below a collapsed node are not visible.
+    pub(crate) fn layout(&self, collapsed: &[bool]) -> Vec<Option<(f64, usize)>> {
+        let n = self.nodes.len();
+        let children = |i: usize| -> &[usize] {
+            if collapsed[i] {
+                &[]
+            } else {
+                &self.nodes[i].children
+            }
+        };
+
+        // offsets of each node relative to its parent, and the contours of each subtree relative to its root,
+        // found in post-order which is the reverse of the BFS order
+        let mut offset = vec![0.0; n];
+        let mut contours: Vec<Option<(Vec<f64>, Vec<f64>)>> = vec![None; n];
+        for i in (0..n).rev() {
+            let mut left: Vec<f64> = vec![];
+            let mut right: Vec<f64> = vec![];
+            let mut places = vec![];
+            for &c in children(i) {
+                let (c_left, c_right) = contours[c].take().unwrap();
+                let shift = right
+                    .iter()
+                    .zip(&c_left)
+                    .map(|(r, l)| r - l + 1.0)
+                    .fold(0.0, f64::max);
+                let shift = if places.is_empty() { 0.0 } else { shift };
+
+                for (d, (l, r)) in c_left.iter().zip(&c_right).enumerate() {
+                    if d >= left.len() {
+                        left.push(l + shift);
+                        right.push(r + shift);
+                    } else {
+                        right[d] = r + shift;
+                    }
+                }
+                places.push((c, shift));
+            }
+
+            let mid = match (places.first(), places.last()) {
+                (Some((_, first)), Some((_, last))) => (first + last) / 2.0,
+                _ => 0.0,
+            };
+            for (c, shift) in places {
+                offset[c] = shift - mid;
+            }
+            contours[i] = Some((
+                std::iter::once(0.0)
+                    .chain(left.iter().map(|x| x - mid))
+                    .collect(),
+                std::iter::once(0.0)
+                    .chain(right.iter().map(|x| x - mid))
+                    .collect(),
+            ));
+        }
+
+        // absolute positions in pre-order, which is the BFS order
+        let mut ans: Vec<Option<(f64, usize)>> = vec![None; n];
+        ans[0] = Some((0.0, 0));
+        for i in 0..n {
+            if let Some((x, depth)) = ans[i] {
+                for &c in children(i) {
+                    ans[c] = Some((x + offset[c], depth + 1));
+                }
+            }
+        }
+
+        let min = ans.iter().flatten().map(|(x, _)| *x).fold(0.0, f64::min);
+        ans.iter()
+            .map(|pos| pos.map(|(x, depth)| (x - min, depth)))
+            .collect()
+    }
+
+    /// Draws the tree as text with box-drawing characters, one node per line with the direction taken to it.
+    ///
+    /// With a focus, only the nodes on the [`route`](Tree::route) to it are expanded,
+    /// and the other nodes that have children are marked with `…`. Nodes whose children
+    /// were left out of the tree, as in [`Tree::riptree_towards`], are marked the same way.
+    pub fn to_text(&self, labels: &[Label], focus: Option<&Path>) -> String {
+        let route = focus.map(|p| self.route(p));
+        let mut ans = String::new();
+
+        // DFS in pre-order, with the connector of each node and the indent of its children
+        let mut stack = vec![(0, String::new(), String::new())];
+        while let Some((i, connector, indent)) = stack.pop() {
+            let node = &self.nodes[i];
+            let expanded = route.as_ref().map_or(true, |route| route.contains(&i));
+
+            ans.push_str(&connector);
+            if let Some((_, dir)) = node.parent {
+                ans.push_str(edge_label(dir));
+                ans.push(' ');
+            }
+            ans.push_str(&node.label(labels).join(" "));
+            if node.hidden || (!expanded && !node.children.is_empty()) {
+                ans.push_str(" …");
+            }
+            ans.push('\n');
+
+            if expanded {
+                let last = node.children.len().saturating_sub(1);
+                for (k, &c) in node.children.iter().enumerate().rev() {
+                    let (connector, next) = if k == last {
+                        ("└── ", "    ")
+                    } else {
+                        ("├── ", "│   ")
+                    };
+                    stack.push((
+                        c,
+                        format!("{}{}", indent, connector),
+                        format!("{}{}", indent, next),
+                    ));
+                }
+            }
+        }
+
+        ans
+    }
+
+    /// Number of nodes in the subtree of a node, including itself.
+    fn subtree_size(&self, i: usize) -> usize {
+        1 + self.nodes[i]
+            .children
+            .iter()
+            .map(|c| self.subtree_size(*c))
+            .sum::<usize>()
+    }
+}
+
+/// Snapshot of a RIPTree node, without its links.
+fn rip_snapshot(node: &RipNode) -> TreeNode {
+    TreeNode {
+        n: node.n().clone(),
+        path: node.path().clone(),
+        prefix: node.prefix().to_vec(),
+        nature: None,
+        parent: None,
+        children: vec![],
+        hidden: false,
+    }
+}
+
+fn rip_children(node: &RipNode) -> Vec<(bool, RipNode)> {
+    vec![(false, node.left()), (true, node.right())]
+}
+
+/// Snapshot of the node of a PIPTree cursor, without its links.
+fn pip_snapshot(cursor: &PipCursor) -> TreeNode {
+    TreeNode {
+        n: cursor.n().clone(),
+        path: cursor.path().clone(),
+        prefix: cursor.prefix().to_vec(),
+        nature: Some(cursor.nature()),
+        parent: None,
+        children: vec![],
+        hidden: false,
+    }
+}
+
+fn pip_children(cursor: &PipCursor) -> Vec<(bool, PipCursor)> {
+    [false, true]
+        .into_iter()
+        .filter_map(|dir| {
+            let mut child = cursor.clone();
+            child.go(dir).then_some((dir, child))
+        })
+        .collect()
+}
+
+/// Colour of an edge by its direction.
+pub(crate) fn edge_color(dir: bool) -> &'static str {
+    if dir {
+        RIGHT_COLOR
+    } else {
+        LEFT_COLOR
+    }
+}
+
+/// Label of an edge by its direction.
+pub(crate) fn edge_label(dir: bool) -> &'static str {
+    if dir {
+        "R"
+    } else {
+        "L"
+    }
+}
+
+/// Escapes the quotes in a DOT string.
+fn escape_dot(s: &str) -> String {
+    s.replace('"', "\\\"")
+}
+
+/// Escapes the special characters in XML text.
+pub(crate) fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{piptree, riptree, utils::to_path};
+
+    #[test]
+    fn test_tree() {
+        let tree = Tree::riptree(&RipNode::root(), 3);
+        assert_eq!(tree.nodes().len(), 15, "Wrong number of nodes.");
+        for (i, node) in tree.nodes().iter().enumerate() {
+            assert_eq!(
+                node.prefix,
+                riptree::prefix_find(node.n.clone(), &node.path),
+                "Wrong prefix."
+            );
+            for (&child, dir) in node.children.iter().zip([false, true]) {
+                assert_eq!(tree.nodes()[child].parent, Some((i, dir)));
+            }
+        }
+
+        // the whole PIPTree of paths of length 4
+        let tree = Tree::piptree(&PipCursor::root(4), 10);
+        assert_eq!(tree.nodes().len(), 15, "Wrong number of nodes.");
+        assert_eq!(
+            tree.nodes()
+                .iter()
+                .map(|node| node.nature.unwrap())
+                .collect::<Vec<_>>(),
+            piptree::natures(4),
+            "Nodes should be in heap order."
+        );
+        assert_eq!(tree.find(&"LRLR".parse().unwrap()), Some(4));
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let tree = Tree::piptree(&PipCursor::root(2), 1);
+        let labels = [Label::Number, Label::Path, Label::Prefix, Label::Nature];
+
+        assert_eq!(
+            tree.to_dot(&labels),
+            format!(
+                "digraph piptree {{\n  node [shape=box];\n  n0 [label=\"2\\nLR\\n[1]\\nG\"];\n  n1 [label=\"3\\nRL\\n[0, 1]\\nG\"];\n  n2 [label=\"1\\nRR\\n[0]\\nB\"];\n  n0 -> n1 [color=\"{}\", label=\"L\"];\n  n0 -> n2 [color=\"{}\", label=\"R\"];\n}}\n",
+                LEFT_COLOR, RIGHT_COLOR
+            )
+        );
+
+        let graphml = tree.to_graphml(DEFAULT_LABELS);
+        assert!(graphml.contains("<node id=\"n1\"><data key=\"label\">3\n[0, 1]</data></node>"));
+        assert!(graphml.contains(&format!(
+            "<edge source=\"n0\" target=\"n2\"><data key=\"direction\">R</data><data key=\"color\">{}</data></edge>",
+            RIGHT_COLOR
+        )));
+        assert_eq!(graphml.matches("<edge ").count(), 2);
+
+        // RIPTree nodes have no nature
+        let tree = Tree::riptree(&RipNode::root(), 0);
+        assert_eq!(tree.root().label(&labels), vec!["1", "", "[0]"]);
+    }
+
+    #[test]
+    fn test_layout() {
+        let tree = Tree::riptree(&RipNode::root(), 5);
+        let collapsed = vec![false; tree.nodes().len()];
+        let positions = tree.layout(&collapsed);
+        assert!(positions.iter().all(|pos| pos.is_some()));
+
+        for (i, node) in tree.nodes().iter().enumerate() {
+            let (x, depth) = positions[i].unwrap();
+            assert_eq!(depth, node.path.len() - tree.root().path.len());
+            if let [left, right] = node.children[..] {
+                let (l, r) = (positions[left].unwrap().0, positions[right].unwrap().0);
+                assert!(l < x && x < r, "Children should be on either side.");
+                assert!((x - (l + r) / 2.0).abs() < 1e-9, "Node should be centred.");
+            }
+        }
+
+        // nodes at the same depth do not overlap
+        for depth in 0..=5 {
+            let mut xs: Vec<f64> = positions
+                .iter()
+                .flatten()
+                .filter(|(_, d)| *d == depth)
+                .map(|(x, _)| *x)
+                .collect();
+            xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert!(xs.windows(2).all(|w| w[1] - w[0] >= 1.0 - 1e-9));
+        }
+        assert_eq!(
+            positions
+                .iter()
+                .flatten()
+                .map(|(x, _)| *x)
+                .fold(f64::MAX, f64::min),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_svg() {
+        let tree = Tree::riptree(&RipNode::root(), 3);
+        let svg = tree.to_svg(&SvgOptions::default());
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect ").count(), 15);
+        assert_eq!(svg.matches("<line ").count(), 14);
+        assert_eq!(svg.matches(HIGHLIGHT_COLOR).count(), 0);
+
+        // collapse the left subtree of the root, and highlight the route to 7 at RRL
+        let options = SvgOptions {
+            labels: vec![Label::Number],
+            collapsed: vec!["L".parse().unwrap()],
+            highlight: Some("RRL".parse().unwrap()),
+        };
+        let svg = tree.to_svg(&options);
+        assert_eq!(svg.matches("<rect ").count(), 9);
+        assert_eq!(svg.matches("stroke-dasharray").count(), 1);
+        assert!(
+            svg.contains(">+6</text>"),
+            "Collapsed node should count its hidden nodes."
+        );
+        assert_eq!(svg.matches(HIGHLIGHT_COLOR).count(), 4);
+        assert_eq!(svg.matches("stroke-width=\"3\"").count(), 7);
+        assert_eq!(
+            tree.route(&"RRL".parse().unwrap()),
+            vec![0, 2, 6, 13],
+            "Wrong route."
+        );
+
+        // routes in PIPTree follow the root directions
+        let tree = Tree::piptree(&PipCursor::root(4), 3);
+        let route = tree.route(&"RLRR".parse().unwrap());
+        assert_eq!(
+            route
+                .iter()
+                .map(|i| tree.nodes()[*i].path.to_string())
+                .collect::<Vec<_>>(),
+            vec!["LLLR", "LLRL", "LRLR", "RLRR"]
+        );
+        assert!(tree.route(&"LLR".parse().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_text() {
+        let tree = Tree::riptree(&RipNode::root(), 2);
+        assert_eq!(
+            tree.to_text(DEFAULT_LABELS, None),
+            "1 [0]\n\
+             ├── L 2 [1]\n\
+             │   ├── L 4 [2]\n\
+             │   └── R 2 [1]\n\
+             └── R 1 [0]\n    \
+             ├── L 3 [0, 1]\n    \
+             └── R 1 [0]\n"
+        );
+        assert_eq!(
+            tree.to_text(&[Label::Number], Some(&"RL".parse().unwrap())),
+            "1\n\
+             ├── L 2 …\n\
+             └── R 1\n    \
+             ├── L 3\n    \
+             └── R 1\n"
+        );
+
+        let tree = Tree::piptree(&PipCursor::root(2), 1);
+        assert_eq!(
+            tree.to_text(&[Label::Number, Label::Nature], None),
+            "2 G\n├── L 3 G\n└── R 1 B\n"
+        );
+
+        // 3 is at RLRR in the PIPTree of paths of length 4
+        let focus = piptree::find_path(&BigUint::from(3u32), 4).unwrap();
+        let tree = Tree::piptree_towards(&PipCursor::root(4), 4, &focus);
+        assert_eq!(
+            tree.to_text(DEFAULT_LABELS, Some(&focus)),
+            "8 [3]\n\
+             ├── L 12 [2, 3]\n\
+             │   ├── L 14 [1, 2, 3] …\n\
+             │   └── R 6 [1, 2]\n\
+             │       ├── L 11 [0, 1, 3]\n\
+             │       └── R 3 [0, 1]\n\
+             └── R 4 [2] …\n"
+        );
+    }
+
+    #[test]
+    fn test_focus() {
+        // building towards a focus prints the same as hiding the rest of the whole tree
+        for n in [1u32, 2, 3, 6, 11, 27] {
+            let n = BigUint::from(n);
+            let p = to_path(&n);
+            let whole = Tree::riptree(&RipNode::root(), 4);
+            let tree = Tree::riptree_towards(&RipNode::root(), 4, &p);
+            assert_eq!(
+                tree.to_text(DEFAULT_LABELS, Some(&p)),
+                whole.to_text(DEFAULT_LABELS, Some(&p)),
+                "Wrong RIPTree towards {}.",
+                n
+            );
+            assert!(tree.nodes().len() <= 2 * 4 + 1, "Tree should be pruned.");
+
+            let p = piptree::find_path(&n, 5).unwrap();
+            let whole = Tree::piptree(&PipCursor::root(5), 5);
+            let tree = Tree::piptree_towards(&PipCursor::root(5), 5, &p);
+            assert_eq!(
+                tree.to_text(DEFAULT_LABELS, Some(&p)),
+                whole.to_text(DEFAULT_LABELS, Some(&p)),
+                "Wrong PIPTree towards {}.",
+                n
+            );
+            assert_eq!(
+                tree.nodes()[*tree.route(&p).last().unwrap()].n,
+                n,
+                "Route should reach the focus."
+            );
+            assert!(tree.nodes().len() <= 2 * 4 + 1, "Tree should be pruned.");
+        }
+
+        // labels agree with prefix_find, powers of two included
+        for node in Tree::piptree(&PipCursor::root(5), 5).nodes() {
+            assert_eq!(
+                node.prefix,
+                piptree::prefix_find(node.n.clone(), &node.path),
+                "Wrong prefix at {}.",
+                node.path
+            );
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::{collatz::EcfIter, prefix::check_strictly_ascending, CollatzError};
+
+/// The set of numbers whose ECF begins with a given prefix.
+///
+/// The parities of the first `k` steps of the shortcut map `T(n) = (3n+1)/2 | n/2` only depend on `n mod 2^k`,
+/// and a prefix `[p_0, ..., p_j]` fixes exactly the first `p_j + 1` of them. So the set is a residue class
+/// mod `2^(p_j + 1)`, except that the residue itself may reach 1 before the prefix is complete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidueClass {
+    pf: Vec<u32>,
+    residue: BigUint,
+    modulus: BigUint,
+}
+
+impl ResidueClass {
+    /// Finds the residue class of a prefix, rejecting lists that are not strictly ascending.
+    ///
+    /// The residue is built one bit at a time, using `T^i(r + 2^i x) = T^i(r) + 3^a x`
+    /// where `a` is the number of odd steps among the first `i`.
+    pub fn new(pf: &[u32]) -> Result<Self, CollatzError> {
+        check_strictly_ascending(pf)?;
+        let steps = pf.last().map_or(0, |p| p + 1);
+
+        let mut residue = BigUint::zero();
+        let mut t = BigUint::zero(); // T^i(residue)
+        let mut pow3 = BigUint::one(); // 3^a
+        let mut odds = pf.iter().peekable();
+
+        for i in 0..steps {
+            let odd = odds.next_if_eq(&&i).is_some();
+            if t.bit(0) != odd {
+                residue.set_bit(i.into(), true);
+                t += &pow3;
+            }
+
+            if odd {
+                t = (3u32 * t + 1u32) >> 1;
+                pow3 *= 3u32;
+            } else {
+                t >>= 1;
+            }
+        }
+
+        Ok(ResidueClass {
+            pf: pf.to_vec(),
+            residue,
+            modulus: BigUint::one() << steps,
+        })
+    }
+
+    /// The residue of the class, in `[0, modulus)`.
+    pub fn residue(&self) -> &BigUint {
+        &self.residue
+    }
+
+    /// The modulus of the class, which is `2^(p_j + 1)` for a prefix `[p_0, ..., p_j]`.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Returns `true` if the ECF of `n` begins with the prefix.
+    pub fn contains(&self, n: &BigUint) -> bool {
+        !n.is_zero()
+            && EcfIter::new(n.clone())
+                .take(self.pf.len())
+                .eq(self.pf.iter().copied())
+    }
+
+    /// Returns the smallest number whose ECF begins with the prefix.
+    ///
+    /// This is the residue, unless it is 0 or it reaches 1 too early, in which case it is the next member of the class.
+    /// A number of at least `2^(p_j + 1)` cannot reach 1 within `p_j` steps of the shortcut map, so it is always a member.
+    pub fn smallest(&self) -> BigUint {
+        if self.contains(&self.residue) {
+            self.residue.clone()
+        } else {
+            &self.residue + &self.modulus
+        }
+    }
+
+    /// Iterates over every number whose ECF begins with the prefix, in ascending order.
+    pub fn members(&self) -> impl Iterator<Item = BigUint> {
+        let modulus = self.modulus.clone();
+        std::iter::successors(Some(self.smallest()), move |n| Some(n + &modulus))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collatz, prefix};
+    use num_bigint::ToBigUint;
+
+    #[test]
+    fn test_residue_class() {
+        struct Case {
+            pf: Vec<u32>,
+            residue: u32,
+            modulus: u32,
+            smallest: u32,
+        }
+        let cases = vec![
+            // edge
+            Case {
+                pf: vec![],
+                residue: 0,
+                modulus: 1,
+                smallest: 1,
+            },
+            Case {
+                pf: vec![0],
+                residue: 1,
+                modulus: 2,
+                smallest: 1,
+            },
+            // power of two
+            Case {
+                pf: vec![4],
+                residue: 16,
+                modulus: 32,
+                smallest: 16,
+            },
+            // residue reaches 1 before the prefix is complete
+            Case {
+                pf: vec![0, 2],
+                residue: 1,
+                modulus: 8,
+                smallest: 9,
+            },
+            // ECF of 3
+            Case {
+                pf: vec![0, 1, 5],
+                residue: 3,
+                modulus: 64,
+                smallest: 3,
+            },
+        ];
+        for case in cases {
+            let class = ResidueClass::new(&case.pf).unwrap();
+            assert_eq!(
+                *class.residue(),
+                case.residue.to_biguint().unwrap(),
+                "Wrong residue of {:?}.",
+                case.pf
+            );
+            assert_eq!(*class.modulus(), case.modulus.to_biguint().unwrap());
+            assert_eq!(class.smallest(), case.smallest.to_biguint().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_residue_class_members() {
+        let end = 1u32 << 12;
+        for pf in [vec![0, 1], vec![1, 3, 4], vec![0, 2, 3, 6], vec![5, 7]] {
+            let expected: Vec<BigUint> = (1..end)
+                .map(BigUint::from)
+                .filter(|n| collatz::ecf(n.clone()).starts_with(&pf))
+                .collect();
+            let members: Vec<BigUint> = ResidueClass::new(&pf)
+                .unwrap()
+                .members()
+                .take_while(|n| *n < BigUint::from(end))
+                .collect();
+            assert_eq!(members, expected, "Wrong members of {:?}.", pf);
+        }
+
+        // inverse of prefix finding
+        let n = 27.to_biguint().unwrap();
+        let m = 31.to_biguint().unwrap();
+        let pf = prefix::find(n.clone(), m.clone());
+        let class = ResidueClass::new(&pf).unwrap();
+        assert!(class.contains(&n) && class.contains(&m));
+        assert_eq!(&n % class.modulus(), &m % class.modulus());
+
+        assert_eq!(
+            ResidueClass::new(&[0, 5, 1]),
+            Err(CollatzError::NotAscending { index: 2 })
+        );
+        assert_eq!(
+            ResidueClass::new(&[0, 1, 1]),
+            Err(CollatzError::NotAscending { index: 2 })
+        );
+    }
+}
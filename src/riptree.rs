@@ -1,41 +1,202 @@
+use std::{collections::VecDeque, iter::FusedIterator};
+
 use num_bigint::BigUint;
 use num_traits::One;
 
 use crate::{
+    path::Path,
     prefix,
-    utils::{from_path, is_pow2},
+    utils::{from_path, is_pow2, to_path},
+    CollatzError,
 };
 
 /// Finds the next number that resides at the path of `n`.
 ///
 /// The path is also given, as `n` can be in different paths (see path extension).
 #[inline]
-pub fn next_in_path(n: BigUint, p: &Vec<bool>) -> BigUint {
+pub fn next_in_path(n: BigUint, p: &Path) -> BigUint {
     n + (BigUint::one() << p.len())
 }
 
 // Finds the prefix of a number, or a number at the given path.
 //
 // If you only care about the number, simply pass NTOP(n) as the path.
-pub fn prefix_find(mut n: BigUint, p: &Vec<bool>) -> Vec<u32> {
+pub fn prefix_find(n: BigUint, p: &Path) -> Vec<u32> {
     assert_eq!(from_path(p), n, "Number must be at this path.");
 
     if is_pow2(&n) {
-        let mut ans = 0;
-        while n > BigUint::one() {
-            n >>= 1;
-            ans += 1;
-        }
-        vec![ans]
+        // the exponent is the run of even steps to 1
+        vec![n.trailing_zeros().unwrap() as u32]
     } else {
         prefix::find(n.clone(), next_in_path(n, p))
     }
 }
 
+/// Fallible version of [`prefix_find`], rejecting a number that is not at the given path.
+pub fn try_prefix_find(n: BigUint, p: &Path) -> Result<Vec<u32>, CollatzError> {
+    if from_path(p) != n {
+        return Err(CollatzError::PathMismatch);
+    }
+
+    Ok(prefix_find(n, p))
+}
+
+/// A node in the RIPTree, holding its number, path and prefix.
+///
+/// The root is 1 at the empty path. Going left appends `L` to the path, which moves to the next number
+/// in the path of the parent (see [`next_in_path`]). Going right appends `R`, which keeps the number
+/// and extends its path (see path extension).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RipNode {
+    n: BigUint,
+    path: Path,
+    prefix: Vec<u32>,
+}
+
+impl RipNode {
+    /// Returns the root of the tree.
+    pub fn root() -> Self {
+        RipNode::at(Path::new())
+    }
+
+    /// Returns the node at a path.
+    pub fn at(path: Path) -> Self {
+        RipNode::with(from_path(&path), path)
+    }
+
+    /// Returns the node of a number, at the path of the number.
+    pub fn of(n: BigUint) -> Self {
+        let path = to_path(&n);
+        RipNode::with(n, path)
+    }
+
+    /// Creates a node from a number and its path, finding the prefix.
+    fn with(n: BigUint, path: Path) -> Self {
+        let prefix = prefix_find(n.clone(), &path);
+        RipNode { n, path, prefix }
+    }
+
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn prefix(&self) -> &[u32] {
+        &self.prefix
+    }
+
+    /// Number of edges from the root, which is the length of the path.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// Returns the parent, unless this is the root.
+    pub fn parent(&self) -> Option<Self> {
+        let mut path = self.path.clone();
+        let n = match path.pop()? {
+            true => self.n.clone(),
+            false => &self.n - (BigUint::one() << path.len()),
+        };
+        Some(RipNode::with(n, path))
+    }
+
+    /// Returns the left child, i.e. the next number in the path of this node.
+    pub fn left(&self) -> Self {
+        let mut path = self.path.clone();
+        path.push(false);
+        RipNode::with(next_in_path(self.n.clone(), &self.path), path)
+    }
+
+    /// Returns the right child, i.e. the same number with its path extended.
+    pub fn right(&self) -> Self {
+        let mut path = self.path.clone();
+        path.push(true);
+        RipNode::with(self.n.clone(), path)
+    }
+
+    /// Returns the other child of the parent, unless this is the root.
+    pub fn sibling(&self) -> Option<Self> {
+        let mut path = self.path.clone();
+        let dir = path.pop()?;
+        let n = match dir {
+            true => &self.n + (BigUint::one() << path.len()),
+            false => &self.n - (BigUint::one() << path.len()),
+        };
+        path.push(!dir);
+        Some(RipNode::with(n, path))
+    }
+
+    /// Iterates over the subtree of this node in BFS order, down to `depth` levels below it.
+    pub fn bfs(&self, depth: usize) -> Bfs {
+        Bfs {
+            queue: VecDeque::from([self.clone()]),
+            end: self.depth() + depth,
+        }
+    }
+
+    /// Iterates over the subtree of this node in DFS pre-order, left first, down to `depth` levels below it.
+    pub fn dfs(&self, depth: usize) -> Dfs {
+        Dfs {
+            stack: vec![self.clone()],
+            end: self.depth() + depth,
+        }
+    }
+}
+
+/// BFS iterator over a subtree of the RIPTree, see [`RipNode::bfs`].
+#[derive(Debug, Clone)]
+pub struct Bfs {
+    queue: VecDeque<RipNode>,
+    end: usize,
+}
+
+impl Iterator for Bfs {
+    type Item = RipNode;
+
+    fn next(&mut self) -> Option<RipNode> {
+        let node = self.queue.pop_front()?;
+        if node.depth() < self.end {
+            self.queue.push_back(node.left());
+            self.queue.push_back(node.right());
+        }
+        Some(node)
+    }
+}
+
+impl FusedIterator for Bfs {}
+
+/// DFS iterator over a subtree of the RIPTree, see [`RipNode::dfs`].
+#[derive(Debug, Clone)]
+pub struct Dfs {
+    stack: Vec<RipNode>,
+    end: usize,
+}
+
+impl Iterator for Dfs {
+    type Item = RipNode;
+
+    fn next(&mut self) -> Option<RipNode> {
+        let node = self.stack.pop()?;
+        if node.depth() < self.end {
+            self.stack.push(node.right());
+            self.stack.push(node.left());
+        }
+        Some(node)
+    }
+}
+
+impl FusedIterator for Dfs {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::to_path;
     use num_bigint::ToBigUint;
 
     #[test]
@@ -96,6 +257,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_prefix_find() {
+        let n = 27.to_biguint().unwrap();
+        assert_eq!(
+            try_prefix_find(n.clone(), &to_path(&n)),
+            Ok(vec![0, 1, 3, 4])
+        );
+        assert_eq!(
+            try_prefix_find(n.clone(), &to_path(&(n + BigUint::one()))),
+            Err(CollatzError::PathMismatch)
+        );
+    }
+
     #[test]
     fn test_next_in_path() {
         struct Case {
@@ -132,4 +306,71 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_rip_node() {
+        let root = RipNode::root();
+        assert_eq!(*root.n(), BigUint::one());
+        assert_eq!(root.prefix(), &[0]);
+        assert!(root.parent().is_none() && root.sibling().is_none());
+
+        // 1 -L-> 2 -R-> 2 -L-> 6 -L-> 14
+        let node = root.left().right().left().left();
+        assert_eq!(node.path().to_string(), "LRLL");
+        assert_eq!(*node.n(), 14.to_biguint().unwrap());
+        assert_eq!(node, RipNode::at(node.path().clone()));
+        assert_eq!(node.depth(), 4);
+
+        let sibling = node.sibling().unwrap();
+        assert_eq!(sibling.path().to_string(), "LRLR");
+        assert_eq!(*sibling.n(), 6.to_biguint().unwrap());
+        assert_eq!(sibling.sibling(), Some(node.clone()));
+        assert_eq!(node.parent(), sibling.parent());
+        assert_eq!(node.parent().unwrap().left(), node);
+
+        let n = 27.to_biguint().unwrap();
+        let node = RipNode::of(n.clone());
+        assert_eq!(node.prefix(), prefix_find(n.clone(), &to_path(&n)));
+        assert_eq!(node.right().prefix().len(), node.prefix().len() + 1);
+    }
+
+    #[test]
+    fn test_rip_traversals() {
+        let root = RipNode::root();
+        let bfs: Vec<RipNode> = root.bfs(4).collect();
+        let dfs: Vec<RipNode> = root.dfs(4).collect();
+        assert_eq!(bfs.len(), 31, "Wrong number of nodes.");
+        assert_eq!(dfs.len(), 31, "Wrong number of nodes.");
+
+        assert!(bfs.windows(2).all(|w| w[0].depth() <= w[1].depth()));
+        assert_eq!(
+            bfs.iter()
+                .take(3)
+                .map(|x| x.path().to_string())
+                .collect::<Vec<_>>(),
+            vec!["", "L", "R"]
+        );
+        assert_eq!(
+            dfs.iter()
+                .take(3)
+                .map(|x| x.path().to_string())
+                .collect::<Vec<_>>(),
+            vec!["", "L", "LL"]
+        );
+
+        for node in bfs {
+            assert_eq!(node.n(), &from_path(node.path()), "Wrong number at path.");
+            assert_eq!(
+                node.prefix(),
+                prefix_find(node.n().clone(), node.path()),
+                "Wrong prefix."
+            );
+            if let Some(parent) = node.parent() {
+                assert!(parent.left() == node || parent.right() == node);
+            }
+        }
+
+        let node = RipNode::of(5.to_biguint().unwrap());
+        assert!(node.dfs(3).all(|x| x.depth() <= node.depth() + 3));
+    }
 }
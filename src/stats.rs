@@ -0,0 +1,195 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::{
+    backend::{Backend, Number},
+    CollatzIteration,
+};
+
+/// Statistics of the trajectory of a number, all gathered in a single iteration.
+///
+/// Steps are counted in the Collatz function, except for [`stopping_time`](Self::stopping_time)
+/// which is counted in the shortcut map `T(n) = (3n+1)/2 | n/2` as in Terras' definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrajectoryStats {
+    /// Number of steps to reach 1, same as [`crate::collatz::length`].
+    pub total_stopping_time: usize,
+    /// Number of shortcut steps to drop below the starting number, if it ever does.
+    pub stopping_time: Option<usize>,
+    /// Number of steps to drop below the starting number, if it ever does.
+    pub glide: Option<usize>,
+    /// Largest number in the trajectory, same as [`crate::collatz::height`].
+    pub max_excursion: BigUint,
+    /// Number of steps to first reach the largest number.
+    pub max_excursion_step: usize,
+    /// Number of `3n+1` steps.
+    pub odd_steps: usize,
+    /// Number of `n/2` steps.
+    pub even_steps: usize,
+    /// Length of the ECF, same as the length of [`crate::collatz::ecf`].
+    pub ecf_length: usize,
+}
+
+impl TrajectoryStats {
+    /// Iterates a number until 1 is reached, keeping track of every statistic along the way.
+    ///
+    /// Panics if `n` is zero.
+    pub fn of(n: BigUint) -> Self {
+        assert!(!n.is_zero(), "Number must be positive.");
+
+        let start = n.clone();
+        let mut stats = TrajectoryStats {
+            total_stopping_time: 0,
+            stopping_time: None,
+            glide: None,
+            max_excursion: n.clone(),
+            max_excursion_step: 0,
+            odd_steps: 0,
+            even_steps: 0,
+            ecf_length: 1,
+        };
+
+        let mut n = Number::from(n);
+        while !n.is_one() {
+            if n.is_odd() {
+                n.three_x_plus_one();
+                stats.odd_steps += 1;
+                stats.ecf_length += 1;
+
+                // a peak can only be reached right after an odd step
+                if n.bits() >= stats.max_excursion.bits() {
+                    let x = n.to_big();
+                    if x > stats.max_excursion {
+                        stats.max_excursion = x;
+                        stats.max_excursion_step = stats.odd_steps + stats.even_steps;
+                    }
+                }
+            } else if stats.glide.is_some() {
+                // jump over the whole run of even steps
+                stats.even_steps += n.shr_trailing_zeros() as usize;
+            } else {
+                // step one at a time, as the drop may happen within the run
+                n.shr(1);
+                stats.even_steps += 1;
+                if n.bits() < start.bits() || (n.bits() == start.bits() && n.to_big() < start) {
+                    stats.glide = Some(stats.odd_steps + stats.even_steps);
+                    // every odd step is merged with the halving after it
+                    stats.stopping_time = Some(stats.even_steps);
+                }
+            }
+        }
+
+        stats.total_stopping_time = stats.odd_steps + stats.even_steps;
+        stats
+    }
+}
+
+impl fmt::Display for TrajectoryStats {
+    /// Writes the statistics one per line, where a missing value is written as `-`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_dash = |x: Option<usize>| x.map_or("-".to_string(), |x| x.to_string());
+        writeln!(f, "total stopping time: {}", self.total_stopping_time)?;
+        writeln!(f, "stopping time: {}", or_dash(self.stopping_time))?;
+        writeln!(f, "glide: {}", or_dash(self.glide))?;
+        writeln!(
+            f,
+            "max excursion: {} at step {}",
+            self.max_excursion, self.max_excursion_step
+        )?;
+        writeln!(f, "odd steps: {}", self.odd_steps)?;
+        writeln!(f, "even steps: {}", self.even_steps)?;
+        write!(f, "ecf length: {}", self.ecf_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collatz;
+    use num_bigint::ToBigUint;
+    use num_traits::One;
+
+    #[test]
+    fn test_stats() {
+        struct Case {
+            n: BigUint,
+            stats: TrajectoryStats,
+        }
+        let cases = vec![
+            // edge
+            Case {
+                n: BigUint::one(),
+                stats: TrajectoryStats {
+                    total_stopping_time: 0,
+                    stopping_time: None,
+                    glide: None,
+                    max_excursion: BigUint::one(),
+                    max_excursion_step: 0,
+                    odd_steps: 0,
+                    even_steps: 0,
+                    ecf_length: 1,
+                },
+            },
+            // even number
+            Case {
+                n: 12.to_biguint().unwrap(),
+                stats: TrajectoryStats {
+                    total_stopping_time: 9,
+                    stopping_time: Some(1),
+                    glide: Some(1),
+                    max_excursion: 16.to_biguint().unwrap(),
+                    max_excursion_step: 5,
+                    odd_steps: 2,
+                    even_steps: 7,
+                    ecf_length: 3,
+                },
+            },
+            // large sequence https://oeis.org/A008884
+            Case {
+                n: 27.to_biguint().unwrap(),
+                stats: TrajectoryStats {
+                    total_stopping_time: 111,
+                    stopping_time: Some(59),
+                    glide: Some(96),
+                    max_excursion: 9232.to_biguint().unwrap(),
+                    max_excursion_step: 77,
+                    odd_steps: 41,
+                    even_steps: 70,
+                    ecf_length: 42,
+                },
+            },
+        ];
+        for case in cases {
+            assert_eq!(TrajectoryStats::of(case.n), case.stats, "Wrong statistics.");
+        }
+    }
+
+    #[test]
+    fn test_stats_sequence() {
+        for n in (1..1000u32).map(BigUint::from) {
+            let seq = collatz::sequence(n.clone());
+            let stats = TrajectoryStats::of(n.clone());
+
+            let glide = seq.iter().position(|x| *x < n);
+            let max = seq.iter().max().unwrap();
+            assert_eq!(stats.glide, glide, "Wrong glide for {}.", n);
+            assert_eq!(
+                stats.stopping_time,
+                glide.map(|k| seq[..k].iter().filter(|x| !x.bit(0)).count()),
+                "Wrong stopping time for {}.",
+                n
+            );
+            assert_eq!(&stats.max_excursion, max, "Wrong max excursion for {}.", n);
+            assert_eq!(
+                stats.max_excursion_step,
+                seq.iter().position(|x| x == max).unwrap(),
+                "Wrong max excursion step for {}.",
+                n
+            );
+            assert_eq!(stats.total_stopping_time, seq.len() - 1);
+            assert_eq!(stats.ecf_length, collatz::ecf(n).len());
+        }
+    }
+}
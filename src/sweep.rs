@@ -0,0 +1,300 @@
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use rayon::prelude::*;
+
+use crate::{
+    collatz::{self, LengthTable},
+    iterative::{self, PrefixFinder},
+    piptree, riptree,
+    utils::to_path,
+};
+
+/// Default number of consecutive numbers handed to a thread at once.
+pub const DEFAULT_CHUNK: usize = 1 << 12;
+
+/// Largest [`LengthTable`] built for a sweep of lengths.
+pub const DEFAULT_TABLE: usize = 1 << 20;
+
+/// Splits `[start, end]` into chunks of `chunk` numbers, and maps each chunk on all cores.
+///
+/// A chunk is processed by a single thread, from its first number to its last.
+/// The results are concatenated in the order of the chunks, so the output is the same as a sequential run.
+///
+/// Panics if `chunk` is zero, or the range has more than `usize::MAX` numbers.
+pub fn map_chunks<T, F>(start: &BigUint, end: &BigUint, chunk: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(BigUint, BigUint) -> Vec<T> + Sync,
+{
+    assert!(chunk > 0, "Chunk size must be positive.");
+    if start > end {
+        return vec![];
+    }
+
+    let count = range_count(start, end);
+    (0..count.div_ceil(chunk))
+        .into_par_iter()
+        .map(|i| {
+            let (lo, hi) = chunk_bounds(start, count, chunk, i);
+            f(lo, hi)
+        })
+        .collect::<Vec<Vec<T>>>()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Streaming version of [`map_chunks`], handing the output of each chunk to `sink` in the order of the chunks.
+///
+/// Chunks are mapped on all cores one batch at a time, with as many chunks in a batch as there are threads.
+/// Only the outputs of a single batch are kept in memory, no matter the size of the range.
+/// The sweep stops at the first error returned by `sink`, which is passed on.
+///
+/// Panics if `chunk` is zero, or the range has more than `usize::MAX` numbers.
+pub fn try_for_each_chunk<T, E, F, G>(
+    start: &BigUint,
+    end: &BigUint,
+    chunk: usize,
+    f: F,
+    mut sink: G,
+) -> Result<(), E>
+where
+    T: Send,
+    F: Fn(BigUint, BigUint) -> Vec<T> + Sync,
+    G: FnMut(Vec<T>) -> Result<(), E>,
+{
+    assert!(chunk > 0, "Chunk size must be positive.");
+    if start > end {
+        return Ok(());
+    }
+
+    let count = range_count(start, end);
+    let chunks = count.div_ceil(chunk);
+    let batch = rayon::current_num_threads().max(1);
+    for first in (0..chunks).step_by(batch) {
+        (first..(first + batch).min(chunks))
+            .into_par_iter()
+            .map(|i| {
+                let (lo, hi) = chunk_bounds(start, count, chunk, i);
+                f(lo, hi)
+            })
+            .collect::<Vec<Vec<T>>>()
+            .into_iter()
+            .try_for_each(&mut sink)?;
+    }
+    Ok(())
+}
+
+/// Streaming version of [`map`], handing the output of each number to `sink` in order.
+///
+/// The sweep stops at the first error returned by `sink`, which is passed on.
+pub fn try_for_each<T, E, F, G>(start: &BigUint, end: &BigUint, f: F, mut sink: G) -> Result<(), E>
+where
+    T: Send,
+    F: Fn(BigUint) -> T + Sync,
+    G: FnMut(T) -> Result<(), E>,
+{
+    try_for_each_chunk(
+        start,
+        end,
+        DEFAULT_CHUNK,
+        |lo, hi| {
+            std::iter::successors(Some(lo), |n| Some(n + 1u32))
+                .take_while(|n| *n <= hi)
+                .map(&f)
+                .collect()
+        },
+        |xs| xs.into_iter().try_for_each(&mut sink),
+    )
+}
+
+/// Maps every number in `[start, end]` on all cores, in chunks of [`DEFAULT_CHUNK`] numbers.
+///
+/// The output is ordered by the numbers.
+pub fn map<T, F>(start: &BigUint, end: &BigUint, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(BigUint) -> T + Sync,
+{
+    map_chunks(start, end, DEFAULT_CHUNK, |lo, hi| {
+        std::iter::successors(Some(lo), |n| Some(n + 1u32))
+            .take_while(|n| *n <= hi)
+            .map(&f)
+            .collect()
+    })
+}
+
+/// Table of lengths to share across all threads when sweeping up to `end`.
+///
+/// It covers the range up to `end`, but holds at most [`DEFAULT_TABLE`] lengths.
+pub fn length_table(end: &BigUint) -> LengthTable {
+    let bound = end.to_usize().map_or(DEFAULT_TABLE, |end| {
+        end.saturating_add(1).min(DEFAULT_TABLE)
+    });
+    LengthTable::new(bound)
+}
+
+/// Collatz lengths of every number in `[start, end]`, see [`collatz::length`].
+///
+/// A single [`LengthTable`] is built up front, and shared read-only by every thread.
+pub fn lengths(start: &BigUint, end: &BigUint) -> Vec<usize> {
+    let table = length_table(end);
+    map(start, end, |n| table.length(n))
+}
+
+/// ECFs of every number in `[start, end]`, see [`collatz::ecf`].
+pub fn ecfs(start: &BigUint, end: &BigUint) -> Vec<Vec<u32>> {
+    map(start, end, collatz::ecf)
+}
+
+/// RIPTree prefixes of every number in `[start, end]`, see [`riptree::prefix_find`].
+pub fn rip_prefixes(start: &BigUint, end: &BigUint) -> Vec<Vec<u32>> {
+    map(start, end, |n| {
+        let p = to_path(&n);
+        riptree::prefix_find(n, &p)
+    })
+}
+
+/// PIPTree prefixes of every number in `[start, end]`, see [`piptree::prefix_find`].
+pub fn pip_prefixes(start: &BigUint, end: &BigUint) -> Vec<Vec<u32>> {
+    map(start, end, |n| {
+        let p = to_path(&n);
+        piptree::prefix_find(n, &p)
+    })
+}
+
+/// ECFs of every number in `[start, end]` found iteratively, see [`iterative::prefix`].
+pub fn iterative_ecfs(
+    start: &BigUint,
+    end: &BigUint,
+    prefix_finder: PrefixFinder,
+) -> Vec<Vec<u32>> {
+    map(start, end, |n| iterative::prefix(&n, prefix_finder))
+}
+
+/// Number of numbers in `[start, end]`, where `start <= end`.
+fn range_count(start: &BigUint, end: &BigUint) -> usize {
+    (end - start + 1u32)
+        .to_usize()
+        .expect("Range must fit in usize.")
+}
+
+/// Bounds of the `i`-th chunk of a range, where the last chunk may be shorter.
+fn chunk_bounds(start: &BigUint, count: usize, chunk: usize, i: usize) -> (BigUint, BigUint) {
+    let lo = start + i * chunk;
+    let hi = start + ((i + 1) * chunk).min(count) - 1u32;
+    (lo, hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::ToBigUint;
+    use num_traits::One;
+
+    #[test]
+    fn test_sweep() {
+        let start = BigUint::one();
+        let end = 10000.to_biguint().unwrap();
+        let nums: Vec<BigUint> = (1..=10000u32).map(BigUint::from).collect();
+
+        assert_eq!(
+            lengths(&start, &end),
+            nums.iter()
+                .cloned()
+                .map(collatz::length)
+                .collect::<Vec<_>>(),
+            "Wrong lengths."
+        );
+
+        let ecf: Vec<Vec<u32>> = nums.iter().cloned().map(collatz::ecf).collect();
+        assert_eq!(ecfs(&start, &end), ecf, "Wrong ECFs.");
+        assert_eq!(
+            iterative_ecfs(&start, &end, riptree::prefix_find),
+            ecf,
+            "Wrong ECFs using Prefix + RIPTree."
+        );
+        assert_eq!(
+            rip_prefixes(&start, &end),
+            pip_prefixes(&start, &end),
+            "RIPTree and PIPTree prefixes should match."
+        );
+    }
+
+    #[test]
+    fn test_sweep_chunks() {
+        let start = 5.to_biguint().unwrap();
+        let end = 16.to_biguint().unwrap();
+        for chunk in [1, 5, 12, 100] {
+            assert_eq!(
+                map_chunks(&start, &end, chunk, |lo, hi| vec![(lo, hi)])
+                    .last()
+                    .map(|(_, hi)| hi.clone()),
+                Some(end.clone()),
+                "Last chunk should end at the range end."
+            );
+            assert_eq!(
+                map(&start, &end, |n| n),
+                (5..=16u32).map(BigUint::from).collect::<Vec<_>>(),
+                "Numbers should be in order."
+            );
+        }
+        assert!(
+            map(&end, &start, |n| n).is_empty(),
+            "Range should be empty."
+        );
+
+        for chunk in [1, 5, 12, 100] {
+            let mut streamed = vec![];
+            let done: Result<(), ()> = try_for_each_chunk(
+                &start,
+                &end,
+                chunk,
+                |lo, hi| vec![(lo, hi)],
+                |xs| {
+                    streamed.extend(xs);
+                    Ok(())
+                },
+            );
+            assert_eq!(done, Ok(()));
+            assert_eq!(
+                streamed,
+                map_chunks(&start, &end, chunk, |lo, hi| vec![(lo, hi)]),
+                "Chunks should be streamed in order."
+            );
+        }
+        let mut streamed = vec![];
+        let done: Result<(), ()> = try_for_each(
+            &start,
+            &end,
+            |n| n,
+            |n| {
+                streamed.push(n);
+                Ok(())
+            },
+        );
+        assert_eq!(done, Ok(()));
+        assert_eq!(
+            streamed,
+            map(&start, &end, |n| n),
+            "Numbers should be streamed in order."
+        );
+
+        // the sweep stops at the first error
+        let mut streamed = vec![];
+        let done = try_for_each(
+            &start,
+            &end,
+            |n| n,
+            |n| {
+                if n == BigUint::from(8u32) {
+                    return Err(n);
+                }
+                streamed.push(n);
+                Ok(())
+            },
+        );
+        assert_eq!(done, Err(BigUint::from(8u32)), "Sweep should stop at 8.");
+        assert_eq!(streamed, (5..8u32).map(BigUint::from).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+
+use crate::collatz;
+
+/// A node of [`PrefixTrie`], labelled by a run of ECF elements.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    label: Vec<u32>,
+    parent: Option<usize>,
+    /// Sorted by the first element of their labels.
+    children: Vec<usize>,
+    /// Numbers whose ECF ends at this node.
+    ids: Vec<usize>,
+}
+
+/// A trie of ECFs, where the prefix of stored numbers can be found as in [`crate::prefix::find`].
+///
+/// Runs of elements without branches are kept in a single node, so there are at most two nodes per number.
+/// Nodes are kept in an arena and refer to each other by index.
+#[derive(Debug, Clone)]
+pub struct PrefixTrie {
+    nodes: Vec<Node>,
+    numbers: Vec<BigUint>,
+    /// Node where the ECF of each number ends.
+    ends: Vec<usize>,
+    index: HashMap<BigUint, usize>,
+}
+
+impl PrefixTrie {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        PrefixTrie {
+            nodes: vec![Node::default()],
+            numbers: vec![],
+            ends: vec![],
+            index: HashMap::new(),
+        }
+    }
+
+    /// Number of stored numbers.
+    pub fn len(&self) -> usize {
+        self.numbers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.numbers.is_empty()
+    }
+
+    /// Returns `true` if the number is stored.
+    pub fn contains(&self, n: &BigUint) -> bool {
+        self.index.contains_key(n)
+    }
+
+    /// Stores a number, finding its ECF.
+    pub fn insert(&mut self, n: BigUint) {
+        let ecf = collatz::ecf(n.clone());
+        self.insert_ecf(n, &ecf);
+    }
+
+    /// Stores a number with its known ECF, such as the output of [`crate::sweep::ecfs`].
+    ///
+    /// A number that is already stored is ignored.
+    pub fn insert_ecf(&mut self, n: BigUint, ecf: &[u32]) {
+        if self.contains(&n) {
+            return;
+        }
+
+        let mut cur = 0;
+        let mut i = 0;
+        while i < ecf.len() {
+            let rest = &ecf[i..];
+            let pos = self.child_position(cur, rest[0]);
+            let child = match pos {
+                Ok(pos) => self.nodes[cur].children[pos],
+                Err(pos) => {
+                    // no stored ECF continues this way, so the rest is a new leaf
+                    let leaf = self.push_node(rest.to_vec(), cur);
+                    self.nodes[cur].children.insert(pos, leaf);
+                    cur = leaf;
+                    break;
+                }
+            };
+
+            let label = &self.nodes[child].label;
+            let common = label.iter().zip(rest).take_while(|(a, b)| a == b).count();
+            if common < label.len() {
+                // the ECF diverges within the label, so split the child there
+                let mid = self.push_node(label[..common].to_vec(), cur);
+                self.nodes[child].label.drain(..common);
+                self.nodes[child].parent = Some(mid);
+                self.nodes[mid].children.push(child);
+                self.nodes[cur].children[pos.unwrap()] = mid;
+                cur = mid;
+            } else {
+                cur = child;
+            }
+            i += common;
+        }
+
+        let id = self.numbers.len();
+        self.nodes[cur].ids.push(id);
+        self.ends.push(cur);
+        self.index.insert(n.clone(), id);
+        self.numbers.push(n);
+    }
+
+    /// Returns the stored numbers whose ECF begins with the prefix, ordered by their ECFs.
+    pub fn with_prefix(&self, pf: &[u32]) -> Vec<&BigUint> {
+        let mut cur = 0;
+        let mut i = 0;
+        while i < pf.len() {
+            let rest = &pf[i..];
+            let child = match self.child_position(cur, rest[0]) {
+                Ok(pos) => self.nodes[cur].children[pos],
+                Err(_) => return vec![],
+            };
+
+            let label = &self.nodes[child].label;
+            let common = label.iter().zip(rest).take_while(|(a, b)| a == b).count();
+            if common < label.len() && common < rest.len() {
+                return vec![];
+            }
+            cur = child;
+            i += common;
+        }
+
+        let mut ans = vec![];
+        self.collect(cur, &mut ans);
+        ans
+    }
+
+    /// Returns the prefix of some stored numbers, the same as [`crate::prefix::find_many`].
+    ///
+    /// This is the common prefix of their ECFs, except that a number which reaches 1 keeps going around
+    /// `1 -> 4 -> 2 -> 1`, as in [`crate::prefix::find`]. So an ECF that ends with `e` goes on with `e+2, e+4, ...`
+    /// for as long as the other ECFs do. If all numbers are equal, the prefix is their ECF.
+    ///
+    /// Returns `None` if the subset is empty, or has a number that is not stored.
+    pub fn common_prefix(&self, subset: &[BigUint]) -> Option<Vec<u32>> {
+        let ends: Vec<usize> = subset
+            .iter()
+            .map(|n| self.index.get(n).map(|id| self.ends[*id]))
+            .collect::<Option<_>>()?;
+        let lca = ends.iter().copied().reduce(|a, b| self.lca(a, b))?;
+
+        // every ECF goes through the common ancestor, so only the cycles at 1 can extend its prefix
+        let mut ans = self.prefix_of(lca);
+        if ends.iter().all(|end| *end == lca) {
+            return Some(ans);
+        }
+        let mut ecfs: Vec<_> = ends
+            .iter()
+            .map(|end| {
+                let ecf = self.prefix_of(*end);
+                let last = *ecf.last().unwrap();
+                ecf.into_iter()
+                    .chain(std::iter::successors(Some(last + 2), |e| Some(e + 2)))
+                    .skip(ans.len())
+            })
+            .collect();
+        loop {
+            let e = ecfs[0].next().unwrap();
+            if !ecfs[1..].iter_mut().all(|ecf| ecf.next() == Some(e)) {
+                return Some(ans);
+            }
+            ans.push(e);
+        }
+    }
+
+    /// Returns the prefixes where the stored ECFs branch, in DFS order.
+    ///
+    /// A prefix is a branching point if at least two stored ECFs continue differently after it,
+    /// where ending there counts as a way to continue.
+    pub fn branch_points(&self) -> Vec<Vec<u32>> {
+        let mut ans = vec![];
+        let mut stack = vec![0];
+        while let Some(cur) = stack.pop() {
+            let node = &self.nodes[cur];
+            if node.children.len() + usize::from(!node.ids.is_empty()) >= 2 {
+                ans.push(self.prefix_of(cur));
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        ans
+    }
+
+    /// Finds the child of a node whose label starts with `e`, or where it would be inserted.
+    fn child_position(&self, node: usize, e: u32) -> Result<usize, usize> {
+        self.nodes[node]
+            .children
+            .binary_search_by_key(&e, |c| self.nodes[*c].label[0])
+    }
+
+    fn push_node(&mut self, label: Vec<u32>, parent: usize) -> usize {
+        self.nodes.push(Node {
+            label,
+            parent: Some(parent),
+            ..Node::default()
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Collects the numbers in the subtree of a node, in DFS order.
+    fn collect<'a>(&'a self, node: usize, ans: &mut Vec<&'a BigUint>) {
+        let mut stack = vec![node];
+        while let Some(cur) = stack.pop() {
+            ans.extend(self.nodes[cur].ids.iter().map(|id| &self.numbers[*id]));
+            stack.extend(self.nodes[cur].children.iter().rev());
+        }
+    }
+
+    /// Returns the nodes from a node up to the root.
+    fn ancestors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(node), |cur| self.nodes[*cur].parent)
+    }
+
+    /// Finds the lowest common ancestor of two nodes.
+    fn lca(&self, a: usize, b: usize) -> usize {
+        let a_path: Vec<usize> = self.ancestors(a).collect();
+        let b_path: Vec<usize> = self.ancestors(b).collect();
+        a_path
+            .iter()
+            .rev()
+            .zip(b_path.iter().rev())
+            .take_while(|(x, y)| x == y)
+            .last()
+            .map_or(0, |(x, _)| *x)
+    }
+
+    /// Returns the prefix from the root to a node, by attaching the labels on the way.
+    fn prefix_of(&self, node: usize) -> Vec<u32> {
+        let mut path: Vec<usize> = self.ancestors(node).collect();
+        path.reverse();
+        path.iter()
+            .flat_map(|cur| self.nodes[*cur].label.iter().copied())
+            .collect()
+    }
+}
+
+impl Default for PrefixTrie {
+    fn default() -> Self {
+        PrefixTrie::new()
+    }
+}
+
+impl FromIterator<BigUint> for PrefixTrie {
+    fn from_iter<I: IntoIterator<Item = BigUint>>(iter: I) -> Self {
+        let mut trie = PrefixTrie::new();
+        for n in iter {
+            trie.insert(n);
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prefix;
+    use num_bigint::ToBigUint;
+
+    /// Maps a given list of numbers to list of biguints.
+    fn to_biguints(nums: Vec<u32>) -> Vec<BigUint> {
+        nums.iter().map(|x| x.to_biguint().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_trie() {
+        let trie: PrefixTrie = (1..2000u32).map(BigUint::from).collect();
+        assert_eq!(trie.len(), 1999);
+
+        for pf in [vec![], vec![0, 1], vec![0, 1, 3], vec![2, 3, 7], vec![5, 7]] {
+            let mut expected: Vec<(Vec<u32>, BigUint)> = (1..2000u32)
+                .map(BigUint::from)
+                .map(|n| (collatz::ecf(n.clone()), n))
+                .filter(|(ecf, _)| ecf.starts_with(&pf))
+                .collect();
+            expected.sort();
+            assert_eq!(
+                trie.with_prefix(&pf),
+                expected.iter().map(|(_, n)| n).collect::<Vec<_>>(),
+                "Wrong numbers with prefix {:?}.",
+                pf
+            );
+        }
+        assert!(trie.with_prefix(&[0, 0]).is_empty());
+
+        for (n, m) in [
+            (3u32, 7u32),
+            (3, 12),
+            (27, 31),
+            (8, 16),
+            (1, 2),
+            (100, 1000),
+        ] {
+            let (n, m) = (n.to_biguint().unwrap(), m.to_biguint().unwrap());
+            assert_eq!(
+                trie.common_prefix(&[n.clone(), m.clone()]),
+                Some(prefix::find(n, m)),
+                "Common prefix should match the prefix of two numbers."
+            );
+        }
+        assert_eq!(
+            trie.common_prefix(&to_biguints(vec![27])),
+            Some(collatz::ecf(27.to_biguint().unwrap()))
+        );
+        for n in 1..60u32 {
+            for m in n + 1..60 {
+                let (n, m) = (n.to_biguint().unwrap(), m.to_biguint().unwrap());
+                assert_eq!(
+                    trie.common_prefix(&[n.clone(), m.clone()]),
+                    Some(prefix::find(n, m)),
+                    "Common prefix should match the prefix of two numbers."
+                );
+            }
+        }
+        for subset in [vec![1, 9], vec![1, 9, 25], vec![3, 7, 11], vec![2, 8, 32]] {
+            let subset = to_biguints(subset);
+            assert_eq!(
+                trie.common_prefix(&subset),
+                Some(prefix::find_many(&subset).0),
+                "Common prefix should match the prefix of many numbers."
+            );
+        }
+        assert_eq!(
+            trie.common_prefix(&to_biguints(vec![1, 9])),
+            Some(vec![0, 2])
+        );
+        assert_eq!(trie.common_prefix(&to_biguints(vec![3, 2000])), None);
+        assert_eq!(trie.common_prefix(&[]), None);
+    }
+
+    #[test]
+    fn test_trie_branches() {
+        // ECFs are [0, 1, 5], [0, 1, 2, ...], [0, 1, 3, ...] and [2, 3, 7]
+        let mut trie: PrefixTrie = to_biguints(vec![3, 7, 11]).into_iter().collect();
+        trie.insert(12.to_biguint().unwrap());
+        trie.insert(3.to_biguint().unwrap());
+        assert_eq!(trie.len(), 4, "Duplicates should be ignored.");
+        assert_eq!(trie.branch_points(), vec![vec![], vec![0, 1]]);
+
+        assert_eq!(
+            trie.common_prefix(&to_biguints(vec![3, 7, 11])),
+            Some(vec![0, 1])
+        );
+        assert_eq!(trie.common_prefix(&to_biguints(vec![3, 12])), Some(vec![]));
+    }
+}
@@ -1,30 +1,22 @@
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 
-/// Finds the path from a number.
+use crate::path::Path;
+
+/// Finds the path from a number, see [`Path::of`].
 ///
-/// 1. Decrement
-/// 2. Convert to binary array
-/// 3. Reverse array
-/// 4. Flip bits
+/// The path is the binary form of `n - 1` with its bits flipped, read from the least significant bit.
 #[inline]
-pub fn to_path(n: &BigUint) -> Vec<bool> {
-    to_binary(&(n.clone() - BigUint::one()))
-        .iter()
-        .rev()
-        .map(|b| !b)
-        .collect()
+pub fn to_path(n: &BigUint) -> Path {
+    Path::of(n)
 }
 
-/// Finds the number from a path.
+/// Finds the number from a path, see [`Path::to_n`].
 ///
-/// 1. Flip bits
-/// 2. Reverse array
-/// 3. Convert to decimal
-/// 4. Increment
+/// This is the inverse of [`to_path`]: the directions are flipped and read as binary from the least significant bit, plus one.
 #[inline]
-pub fn from_path(p: &[bool]) -> BigUint {
-    from_binary(&p.iter().map(|b| !b).rev().collect::<Vec<bool>>()[..]) + BigUint::one()
+pub fn from_path(p: &Path) -> BigUint {
+    p.to_n()
 }
 
 /// Given a binary representation in bools, compute the corresponding number.
@@ -72,17 +64,17 @@ mod tests {
     fn test_path() {
         struct Case {
             n: BigUint,
-            p: Vec<bool>,
+            p: Path,
         }
         let cases = vec![
             // edge
             Case {
                 n: BigUint::one(),
-                p: vec![],
+                p: Path::new(),
             },
             Case {
                 n: 2.to_biguint().unwrap(),
-                p: vec![false],
+                p: [false].into_iter().collect(),
             },
         ];
         for case in cases {